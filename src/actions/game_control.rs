@@ -1,27 +1,116 @@
-use bevy::prelude::{Input, KeyCode, Res};
+use crate::actions::input_map::{Binding, InputMap};
+use crate::actions::touch::TouchControls;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
+const STICK_DEADZONE: f32 = 0.2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub enum GameControl {
     Jump,
     Left,
     Right,
+    Dig,
+    Pause,
+    Back,
 }
 
 impl GameControl {
-    pub fn pressed(&self, keyboard_input: &Res<Input<KeyCode>>) -> bool {
+    pub const ALL: [GameControl; 6] = [
+        GameControl::Jump,
+        GameControl::Left,
+        GameControl::Right,
+        GameControl::Dig,
+        GameControl::Pause,
+        GameControl::Back,
+    ];
+
+    pub fn label(&self) -> &'static str {
         match self {
-            GameControl::Jump => keyboard_input.pressed(KeyCode::Space),
-            GameControl::Left => {
-                keyboard_input.pressed(KeyCode::A) || keyboard_input.pressed(KeyCode::Left)
-            }
-            GameControl::Right => {
-                keyboard_input.pressed(KeyCode::D) || keyboard_input.pressed(KeyCode::Right)
-            }
+            GameControl::Jump => "Jump",
+            GameControl::Left => "Left",
+            GameControl::Right => "Right",
+            GameControl::Dig => "Dig",
+            GameControl::Pause => "Pause",
+            GameControl::Back => "Back",
         }
     }
+
+    /// Menu controls are never read during a run, so they may share bindings with game controls
+    pub fn conflicts_with(&self, other: GameControl) -> bool {
+        (*self == GameControl::Back) == (other == GameControl::Back)
+    }
+}
+
+/// All input sources, read through the bindings of the [`InputMap`]
+#[derive(SystemParam)]
+pub struct ControlInput<'w> {
+    input_map: Res<'w, InputMap>,
+    keyboard_input: Res<'w, Input<KeyCode>>,
+    mouse_input: Res<'w, Input<MouseButton>>,
+    gamepad_input: Res<'w, Input<GamepadButton>>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_axes: Res<'w, Axis<GamepadAxis>>,
+    touch_controls: Res<'w, TouchControls>,
+}
+
+impl ControlInput<'_> {
+    pub fn pressed(&self, control: GameControl) -> bool {
+        self.touch_controls.pressed(control)
+            || self
+                .input_map
+                .bindings(control)
+                .iter()
+                .any(|binding| match *binding {
+                    Binding::Key(key) => self.keyboard_input.pressed(key),
+                    Binding::Mouse(button) => self.mouse_input.pressed(button),
+                    Binding::Gamepad(button) => self.gamepads.iter().any(|gamepad| {
+                        self.gamepad_input
+                            .pressed(GamepadButton::new(gamepad, button))
+                    }),
+                })
+    }
+
+    pub fn just_pressed(&self, control: GameControl) -> bool {
+        self.touch_controls.just_pressed(control)
+            || self
+                .input_map
+                .bindings(control)
+                .iter()
+                .any(|binding| match *binding {
+                    Binding::Key(key) => self.keyboard_input.just_pressed(key),
+                    Binding::Mouse(button) => self.mouse_input.just_pressed(button),
+                    Binding::Gamepad(button) => self.gamepads.iter().any(|gamepad| {
+                        self.gamepad_input
+                            .just_pressed(GamepadButton::new(gamepad, button))
+                    }),
+                })
+    }
+}
+
+impl ControlInput<'_> {
+    /// Horizontal position of the left stick with the deadzone cut out, from -1 to 1
+    ///
+    /// Gamepads are looked up every frame, so controllers connected mid-run are picked up
+    pub fn movement_axis(&self) -> f32 {
+        self.gamepads
+            .iter()
+            .filter_map(|gamepad| {
+                self.gamepad_axes
+                    .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+            })
+            .map(|value| {
+                let magnitude = (value.abs() - STICK_DEADZONE).max(0.) / (1. - STICK_DEADZONE);
+                magnitude.min(1.) * value.signum()
+            })
+            .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+            .unwrap_or(0.)
+    }
 }
 
-pub fn get_movement(control: GameControl, input: &Res<Input<KeyCode>>) -> f32 {
-    if control.pressed(input) {
+pub fn get_movement(control: GameControl, input: &ControlInput) -> f32 {
+    if input.pressed(control) {
         1.0
     } else {
         0.0
@@ -0,0 +1,122 @@
+use crate::actions::game_control::GameControl;
+use crate::settings::{load_config, save_config};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+const INPUT_MAP_FILE: &str = "input.ron";
+
+/// A single physical input that can trigger a [`GameControl`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButtonType),
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{key:?}"),
+            Binding::Mouse(button) => write!(f, "Mouse {button:?}"),
+            Binding::Gamepad(button) => write!(f, "Pad {button:?}"),
+        }
+    }
+}
+
+/// Maps every [`GameControl`] to any number of bindings
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct InputMap(HashMap<GameControl, Vec<Binding>>);
+
+impl Default for InputMap {
+    fn default() -> Self {
+        InputMap(HashMap::from([
+            (
+                GameControl::Jump,
+                vec![
+                    Binding::Key(KeyCode::Space),
+                    Binding::Key(KeyCode::W),
+                    Binding::Gamepad(GamepadButtonType::South),
+                ],
+            ),
+            (
+                GameControl::Left,
+                vec![
+                    Binding::Key(KeyCode::A),
+                    Binding::Key(KeyCode::Left),
+                    Binding::Gamepad(GamepadButtonType::DPadLeft),
+                ],
+            ),
+            (
+                GameControl::Right,
+                vec![
+                    Binding::Key(KeyCode::D),
+                    Binding::Key(KeyCode::Right),
+                    Binding::Gamepad(GamepadButtonType::DPadRight),
+                ],
+            ),
+            (
+                GameControl::Dig,
+                vec![
+                    Binding::Key(KeyCode::S),
+                    Binding::Key(KeyCode::Down),
+                    Binding::Gamepad(GamepadButtonType::West),
+                ],
+            ),
+            (
+                GameControl::Pause,
+                vec![
+                    Binding::Key(KeyCode::Escape),
+                    Binding::Key(KeyCode::P),
+                    Binding::Gamepad(GamepadButtonType::Start),
+                ],
+            ),
+            (
+                GameControl::Back,
+                vec![
+                    Binding::Key(KeyCode::Escape),
+                    Binding::Gamepad(GamepadButtonType::East),
+                ],
+            ),
+        ]))
+    }
+}
+
+impl InputMap {
+    pub fn bindings(&self, control: GameControl) -> &[Binding] {
+        self.0.get(&control).map_or(&[], Vec::as_slice)
+    }
+
+    /// The control that already uses the given binding and would conflict with `control`
+    pub fn bound_to(&self, control: GameControl, binding: Binding) -> Option<GameControl> {
+        GameControl::ALL
+            .into_iter()
+            .filter(|other| other.conflicts_with(control))
+            .find(|other| self.bindings(*other).contains(&binding))
+    }
+
+    /// Add a binding to the control
+    ///
+    /// Fails with the conflicting control if the binding is already in use
+    pub fn bind(&mut self, control: GameControl, binding: Binding) -> Result<(), GameControl> {
+        if let Some(bound) = self.bound_to(control, binding) {
+            return Err(bound);
+        }
+        self.0.entry(control).or_default().push(binding);
+        Ok(())
+    }
+
+    pub fn clear(&mut self, control: GameControl) {
+        self.0.remove(&control);
+    }
+
+    /// Read the input map from the config file, falling back to the default bindings
+    pub fn load() -> Self {
+        load_config(INPUT_MAP_FILE)
+    }
+
+    pub fn save(&self) {
+        save_config(INPUT_MAP_FILE, self)
+    }
+}
@@ -1,17 +1,34 @@
+use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent};
 use bevy::prelude::*;
 
-use crate::actions::game_control::{get_movement, GameControl};
+use crate::actions::game_control::get_movement;
+use crate::physics::PhysicsSystems;
 use crate::player::TakeInputs;
+use crate::replay::Replay;
 use crate::GameState;
 
+pub use crate::actions::game_control::{ControlInput, GameControl};
+pub use crate::actions::input_map::{Binding, InputMap};
+use crate::actions::touch::TouchControlsPlugin;
+
 mod game_control;
+mod input_map;
+mod touch;
 
 pub struct ActionsPlugin;
 
 impl Plugin for ActionsPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<Actions>()
-            .add_system(set_movement_actions.in_set(OnUpdate(GameState::Playing)));
+        app.add_plugin(TouchControlsPlugin)
+            .init_resource::<Actions>()
+            .insert_resource(InputMap::load())
+            .add_system(
+                set_movement_actions
+                    .before(PhysicsSystems::CalculateVelocities)
+                    .in_set(OnUpdate(GameState::Playing))
+                    .run_if(not(resource_exists::<Replay>())),
+            )
+            .add_system(log_gamepad_connections);
     }
 }
 
@@ -19,20 +36,39 @@ impl Plugin for ActionsPlugin {
 pub struct Actions {
     pub player_movement: f32,
     pub attempt_jump: bool,
+    pub attempt_dig: bool,
 }
 
 pub fn set_movement_actions(
     mut actions: ResMut<Actions>,
-    keyboard_input: Res<Input<KeyCode>>,
+    control_input: ControlInput,
     take_inputs: Res<TakeInputs>,
 ) {
     if !take_inputs.0 {
         actions.player_movement = 0.;
         actions.attempt_jump = false;
+        actions.attempt_dig = false;
         return;
     }
 
-    actions.player_movement = get_movement(GameControl::Right, &keyboard_input)
-        - get_movement(GameControl::Left, &keyboard_input);
-    actions.attempt_jump = GameControl::Jump.pressed(&keyboard_input);
+    let digital_movement = get_movement(GameControl::Right, &control_input)
+        - get_movement(GameControl::Left, &control_input);
+    actions.player_movement = if digital_movement != 0. {
+        digital_movement
+    } else {
+        control_input.movement_axis()
+    };
+    actions.attempt_jump = control_input.pressed(GameControl::Jump);
+    actions.attempt_dig = control_input.pressed(GameControl::Dig);
+}
+
+fn log_gamepad_connections(mut gamepad_events: EventReader<GamepadConnectionEvent>) {
+    for event in gamepad_events.iter() {
+        match &event.connection {
+            GamepadConnection::Connected(info) => {
+                info!("Gamepad {} connected: {}", event.gamepad.id, info.name)
+            }
+            GamepadConnection::Disconnected => info!("Gamepad {} disconnected", event.gamepad.id),
+        }
+    }
 }
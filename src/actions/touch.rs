@@ -0,0 +1,157 @@
+use crate::actions::game_control::GameControl;
+use crate::loading::FontAssets;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+pub struct TouchControlsPlugin;
+
+/// This plugin shows on-screen buttons as soon as the game is touched
+/// Each finger is checked against the buttons separately, so running and jumping work together
+impl Plugin for TouchControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TouchControls>()
+            .add_system(detect_touch)
+            .add_system(
+                spawn_touch_controls
+                    .after(detect_touch)
+                    .in_set(OnUpdate(GameState::Playing)),
+            )
+            .add_system(update_touch_controls.in_base_set(CoreSet::PreUpdate))
+            .add_system(despawn_touch_controls.in_schedule(OnExit(GameState::Playing)));
+    }
+}
+
+/// Controls currently held through the on-screen buttons
+#[derive(Resource, Default)]
+pub struct TouchControls {
+    active: bool,
+    pressed: HashSet<GameControl>,
+    just_pressed: HashSet<GameControl>,
+}
+
+impl TouchControls {
+    pub fn pressed(&self, control: GameControl) -> bool {
+        self.pressed.contains(&control)
+    }
+
+    pub fn just_pressed(&self, control: GameControl) -> bool {
+        self.just_pressed.contains(&control)
+    }
+}
+
+#[derive(Component)]
+struct TouchButton(GameControl);
+
+fn detect_touch(
+    mut touch_events: EventReader<TouchInput>,
+    mut touch_controls: ResMut<TouchControls>,
+) {
+    if touch_events.iter().last().is_some() && !touch_controls.active {
+        info!("Touch input detected, showing touch controls");
+        touch_controls.active = true;
+    }
+}
+
+fn spawn_touch_controls(
+    mut commands: Commands,
+    touch_controls: Res<TouchControls>,
+    font_assets: Res<FontAssets>,
+    buttons: Query<(), With<TouchButton>>,
+) {
+    if !touch_controls.active || !buttons.is_empty() {
+        return;
+    }
+    let text_style = TextStyle {
+        font: font_assets.fira_sans.clone(),
+        font_size: 40.0,
+        color: Color::rgba(0.9, 0.9, 0.9, 0.8),
+    };
+    for (control, label, position, size) in [
+        (
+            GameControl::Left,
+            "<",
+            UiRect {
+                left: Val::Percent(2.),
+                bottom: Val::Percent(3.),
+                ..default()
+            },
+            Size::new(Val::Percent(14.), Val::Percent(18.)),
+        ),
+        (
+            GameControl::Right,
+            ">",
+            UiRect {
+                left: Val::Percent(18.),
+                bottom: Val::Percent(3.),
+                ..default()
+            },
+            Size::new(Val::Percent(14.), Val::Percent(18.)),
+        ),
+        (
+            GameControl::Jump,
+            "^",
+            UiRect {
+                right: Val::Percent(2.),
+                bottom: Val::Percent(3.),
+                ..default()
+            },
+            Size::new(Val::Percent(18.), Val::Percent(18.)),
+        ),
+        (
+            GameControl::Pause,
+            "II",
+            UiRect {
+                left: Val::Percent(46.),
+                top: Val::Percent(2.),
+                ..default()
+            },
+            Size::new(Val::Percent(8.), Val::Percent(10.)),
+        ),
+    ] {
+        commands
+            .spawn(NodeBundle {
+                style: Style {
+                    size,
+                    position_type: PositionType::Absolute,
+                    position,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgba(0.15, 0.15, 0.15, 0.4)),
+                ..default()
+            })
+            .insert(TouchButton(control))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(label, text_style.clone()));
+            });
+    }
+}
+
+fn update_touch_controls(
+    touches: Res<Touches>,
+    mut touch_controls: ResMut<TouchControls>,
+    buttons: Query<(&Node, &GlobalTransform, &TouchButton)>,
+) {
+    // touch positions and UI nodes both have their origin in the top left corner
+    let pressed: HashSet<GameControl> = buttons
+        .iter()
+        .filter(|(node, transform, _)| {
+            let rect = Rect::from_center_size(transform.translation().truncate(), node.size());
+            touches.iter().any(|touch| rect.contains(touch.position()))
+        })
+        .map(|(.., button)| button.0)
+        .collect();
+    touch_controls.just_pressed = pressed
+        .difference(&touch_controls.pressed)
+        .copied()
+        .collect();
+    touch_controls.pressed = pressed;
+}
+
+fn despawn_touch_controls(mut commands: Commands, buttons: Query<Entity, With<TouchButton>>) {
+    for entity in &buttons {
+        commands.entity(entity).despawn_recursive();
+    }
+}
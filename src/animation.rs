@@ -0,0 +1,125 @@
+use crate::loading::TextureAssets;
+use crate::player::{update_player_state, Player, PlayerState};
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::BoxedFuture;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+pub struct PlayerAnimationPlugin;
+
+/// This plugin plays the animation clip of the current [`PlayerState`] on the pig
+/// The clips are defined in `textures/pig.clips.ron`
+impl Plugin for PlayerAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<PlayerClips>()
+            .init_asset_loader::<PlayerClipsLoader>()
+            .add_system(
+                animate_player
+                    .after(update_player_state)
+                    .run_if(resource_exists::<TextureAssets>()),
+            );
+    }
+}
+
+/// Frames of the pig sprite sheet shown one after another
+#[derive(Deserialize, Clone, Debug)]
+pub struct Clip {
+    frames: Vec<usize>,
+    frame_seconds: f32,
+    /// Clips that do not loop stop on their last frame
+    #[serde(default)]
+    looping: bool,
+}
+
+#[derive(Deserialize, TypeUuid, Debug)]
+#[uuid = "14a8f148-f6a7-4eef-aee8-391cebeb0d7c"]
+pub struct PlayerClips(HashMap<PlayerState, Clip>);
+
+#[derive(Default)]
+struct PlayerClipsLoader;
+
+impl AssetLoader for PlayerClipsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let clips = ron::de::from_bytes::<PlayerClips>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(clips));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["clips.ron"]
+    }
+}
+
+/// Where the player is in the clip of its state
+#[derive(Component)]
+struct PlayerAnimation {
+    state: PlayerState,
+    frame: usize,
+    elapsed: f32,
+}
+
+fn animate_player(
+    mut commands: Commands,
+    time: Res<Time>,
+    textures: Res<TextureAssets>,
+    clips: Res<Assets<PlayerClips>>,
+    mut player: Query<
+        (
+            Entity,
+            &PlayerState,
+            &mut TextureAtlasSprite,
+            Option<&mut PlayerAnimation>,
+        ),
+        With<Player>,
+    >,
+) {
+    let Some(clips) = clips.get(&textures.pig_clips) else {
+        return;
+    };
+    for (entity, state, mut sprite, animation) in &mut player {
+        let Some(mut animation) = animation else {
+            commands.entity(entity).insert(PlayerAnimation {
+                state: *state,
+                frame: 0,
+                elapsed: 0.,
+            });
+            continue;
+        };
+        if animation.state != *state {
+            animation.state = *state;
+            animation.frame = 0;
+            animation.elapsed = 0.;
+        } else {
+            animation.elapsed += time.delta_seconds();
+        }
+        let Some(clip) = clips
+            .0
+            .get(state)
+            .or_else(|| clips.0.get(&PlayerState::Idle))
+            .filter(|clip| !clip.frames.is_empty())
+        else {
+            sprite.index = 0;
+            continue;
+        };
+        while animation.elapsed >= clip.frame_seconds && clip.frame_seconds > 0. {
+            animation.elapsed -= clip.frame_seconds;
+            if animation.frame + 1 < clip.frames.len() {
+                animation.frame += 1;
+            } else if clip.looping {
+                animation.frame = 0;
+            } else {
+                animation.elapsed = 0.;
+                break;
+            }
+        }
+        sprite.index = clip.frames[animation.frame.min(clip.frames.len() - 1)];
+    }
+}
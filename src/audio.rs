@@ -1,8 +1,30 @@
+use crate::camera::GameCamera;
+use crate::effects::Bird;
 use crate::loading::AudioAssets;
+use crate::music::MusicPlugin;
+use crate::player::{Player, PlayerState, PlayerStateChanged};
+use crate::settings::Settings;
 use crate::GameState;
+use bevy::math::Vec3Swizzles;
 use bevy::prelude::*;
 use bevy_kira_audio::prelude::*;
+use rand::rngs::ThreadRng;
 use rand::{thread_rng, Rng};
+use std::time::Duration;
+
+/// Every sound is played between this much slower and faster
+const PITCH_VARIATION: f64 = 0.1;
+/// Every sound is played between this much quieter and louder
+const VOLUME_VARIATION: f64 = 0.2;
+const FLAPPING_FADE: Duration = Duration::from_millis(400);
+/// Sounds this far to the side of the camera are panned as far as they go
+const PAN_DISTANCE: f32 = 600.;
+/// Keep a bit of every sound on both speakers, 0.5 would be hard left or right
+const MAX_PAN: f64 = 0.4;
+/// Sounds closer to the camera than this play at full volume
+const FULL_VOLUME_DISTANCE: f32 = 400.;
+/// Further away, sounds fade out over this distance
+const FALLOFF_DISTANCE: f32 = 800.;
 
 pub struct InternalAudioPlugin;
 
@@ -10,18 +32,211 @@ pub struct InternalAudioPlugin;
 impl Plugin for InternalAudioPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<LastGrunt>()
+            .init_resource::<Flapping>()
             .add_plugin(AudioPlugin)
-            .add_audio_channel::<Background>()
-            .add_system(start_background.in_schedule(OnEnter(GameState::Menu)))
-            .add_system(random_grunting.in_set(OnUpdate(GameState::Playing)));
+            .add_audio_channel::<UiSounds>()
+            .add_plugin(MusicPlugin)
+            .add_system(random_grunting.in_set(OnUpdate(GameState::Playing)))
+            .add_system(player_state_sounds.before(play_sounds))
+            .add_system(
+                play_sounds
+                    .after(random_grunting)
+                    .run_if(resource_exists::<AudioAssets>()),
+            )
+            .add_system(loop_flapping.run_if(resource_exists::<AudioAssets>()));
     }
 }
 
+/// Menu sounds keep playing while the game audio is paused
 #[derive(Resource)]
-struct Background;
+struct UiSounds;
+
+/// Sent by gameplay and menus for everything that makes a sound
+pub struct SoundEvent {
+    pub sound: Sound,
+    /// Sounds in the level are panned and get quieter the further they are from the camera
+    pub position: Option<Vec2>,
+}
+
+impl SoundEvent {
+    pub fn at(sound: Sound, position: Vec2) -> Self {
+        SoundEvent {
+            sound,
+            position: Some(position),
+        }
+    }
+
+    pub fn centred(sound: Sound) -> Self {
+        SoundEvent {
+            sound,
+            position: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Sound {
+    Grunt,
+    Jump,
+    Land,
+    Eat,
+    Truffle,
+    EffectStart,
+    EffectEnd,
+    BirdSwoop,
+    Death,
+    MenuClick,
+}
+
+/// How a [`Sound`] sounds before the random variation and positioning
+struct SoundPreset {
+    source: Handle<AudioSource>,
+    volume: f64,
+    pitch: f64,
+}
+
+impl Sound {
+    fn preset(&self, audio_assets: &AudioAssets, random: &mut ThreadRng) -> SoundPreset {
+        let (source, volume, pitch) = match self {
+            Sound::Grunt => (audio_assets.random_grunt(random), 0.05, 1.),
+            Sound::Jump => (audio_assets.random_grunt(random), 0.03, 1.4),
+            Sound::Land => (audio_assets.pig3.clone(), 0.02, 0.6),
+            Sound::Eat => (audio_assets.eating.clone(), 0.05, 1.),
+            Sound::Truffle => (audio_assets.eating.clone(), 0.06, 1.5),
+            Sound::EffectStart => (audio_assets.pig2.clone(), 0.05, 1.6),
+            Sound::EffectEnd => (audio_assets.pig2.clone(), 0.04, 0.8),
+            Sound::BirdSwoop => (audio_assets.flying.clone(), 0.1, 1.3),
+            Sound::Death => (audio_assets.random_grunt(random), 0.08, 0.5),
+            Sound::MenuClick => (audio_assets.pig1.clone(), 0.03, 2.),
+        };
+        SoundPreset {
+            source,
+            volume,
+            pitch,
+        }
+    }
+}
+
+/// Volume factor and panning of a sound at `position` for a listener at `listener`
+fn stereo(position: Option<Vec2>, listener: Option<Vec2>) -> (f64, f64) {
+    let (Some(position), Some(listener)) = (position, listener) else {
+        return (1., 0.5);
+    };
+    let offset = position - listener;
+    let fade = ((offset.length() - FULL_VOLUME_DISTANCE) / FALLOFF_DISTANCE).clamp(0., 1.);
+    let pan = (offset.x / PAN_DISTANCE).clamp(-1., 1.);
+    (1. - fade as f64, 0.5 + MAX_PAN * pan as f64)
+}
+
+fn listener(camera: &Query<&Transform, With<GameCamera>>) -> Option<Vec2> {
+    camera
+        .get_single()
+        .ok()
+        .map(|transform| transform.translation.xy())
+}
+
+fn play_sounds(
+    mut sounds: EventReader<SoundEvent>,
+    audio_assets: Res<AudioAssets>,
+    audio: Res<Audio>,
+    ui_audio: Res<AudioChannel<UiSounds>>,
+    settings: Res<Settings>,
+    camera: Query<&Transform, With<GameCamera>>,
+) {
+    let mut random = thread_rng();
+    let listener = listener(&camera);
+    for event in sounds.iter() {
+        let preset = event.sound.preset(&audio_assets, &mut random);
+        let (distance_volume, panning) = stereo(event.position, listener);
+        if distance_volume <= 0. {
+            continue;
+        }
+        let volume = preset.volume
+            * distance_volume
+            * (1. + random.gen_range(-VOLUME_VARIATION..=VOLUME_VARIATION))
+            * settings.sfx_volume();
+        let pitch = preset.pitch * (1. + random.gen_range(-PITCH_VARIATION..=PITCH_VARIATION));
+        let mut command = if event.sound == Sound::MenuClick {
+            ui_audio.play(preset.source)
+        } else {
+            audio.play(preset.source)
+        };
+        command
+            .with_volume(volume)
+            .with_panning(panning)
+            .with_playback_rate(pitch);
+    }
+}
+
+/// The flapping loop while birds are around
+#[derive(Resource, Default)]
+struct Flapping(Option<Handle<AudioInstance>>);
+
+/// Follows the bird closest to the camera
+#[allow(clippy::too_many_arguments)]
+fn loop_flapping(
+    mut flapping: ResMut<Flapping>,
+    birds: Query<&Transform, With<Bird>>,
+    camera: Query<&Transform, With<GameCamera>>,
+    state: Res<State<GameState>>,
+    audio_assets: Res<AudioAssets>,
+    audio: Res<Audio>,
+    mut instances: ResMut<Assets<AudioInstance>>,
+    settings: Res<Settings>,
+) {
+    let listener = listener(&camera);
+    let closest = birds
+        .iter()
+        .map(|bird| bird.translation.xy())
+        .min_by(|a, b| {
+            let distance = |bird: &Vec2| listener.map_or(0., |listener| bird.distance(listener));
+            distance(a).partial_cmp(&distance(b)).unwrap()
+        })
+        .filter(|_| state.0 == GameState::Playing);
+    let Some(bird) = closest else {
+        if let Some(instance) = flapping
+            .0
+            .take()
+            .and_then(|handle| instances.get_mut(&handle))
+        {
+            instance.stop(AudioTween::linear(FLAPPING_FADE));
+        }
+        return;
+    };
+
+    let (distance_volume, panning) = stereo(Some(bird), listener);
+    let volume = 0.05 * distance_volume * settings.sfx_volume();
+    match flapping
+        .0
+        .as_ref()
+        .and_then(|handle| instances.get_mut(handle))
+    {
+        Some(instance) => {
+            instance.set_volume(volume, AudioTween::default());
+            instance.set_panning(panning, AudioTween::default());
+        }
+        None => {
+            let instance = audio
+                .play(audio_assets.flying.clone())
+                .looped()
+                .with_volume(volume)
+                .with_panning(panning)
+                .fade_in(AudioTween::linear(FLAPPING_FADE))
+                .handle();
+            flapping.0 = Some(instance);
+        }
+    }
+}
 
-fn start_background(audio: Res<AudioChannel<Background>>, audio_assets: Res<AudioAssets>) {
-    audio.play(audio_assets.birds.clone()).looped();
+fn player_state_sounds(
+    mut state_changed: EventReader<PlayerStateChanged>,
+    mut sounds: EventWriter<SoundEvent>,
+) {
+    for change in state_changed.iter() {
+        if change.to == PlayerState::Land {
+            sounds.send(SoundEvent::at(Sound::Land, change.position));
+        }
+    }
 }
 
 #[derive(Resource, Default)]
@@ -29,17 +244,17 @@ struct LastGrunt(f32);
 
 fn random_grunting(
     time: Res<Time>,
-    audio_assets: Res<AudioAssets>,
-    audio: Res<Audio>,
     mut last_grunt: ResMut<LastGrunt>,
+    player: Query<&Transform, With<Player>>,
+    mut sounds: EventWriter<SoundEvent>,
 ) {
     let mut random = thread_rng();
     if time.elapsed_seconds() - last_grunt.0 > 1.5
         && random.gen::<f32>() < 0.1 * time.delta_seconds()
     {
         last_grunt.0 = time.elapsed_seconds();
-        audio
-            .play(audio_assets.random_grunt(&mut random))
-            .with_volume(0.05);
+        if let Ok(transform) = player.get_single() {
+            sounds.send(SoundEvent::at(Sound::Grunt, transform.translation.xy()));
+        }
     }
 }
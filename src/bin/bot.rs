@@ -0,0 +1,78 @@
+//! Lets the bot play a batch of seeds without a window and prints how each run went
+//!
+//! `cargo run --bin bot -- --runs 100 --first-seed 0 --max-seconds 600`
+
+use std::collections::BTreeMap;
+use std::env;
+use std::process;
+use truffle_run::simulation::{Simulation, FRAME};
+
+struct Options {
+    runs: u64,
+    first_seed: u64,
+    max_seconds: f32,
+}
+
+fn main() {
+    let options = parse_options().unwrap_or_else(|error| {
+        eprintln!("{error}");
+        eprintln!("Usage: bot [--runs N] [--first-seed SEED] [--max-seconds SECONDS]");
+        process::exit(1);
+    });
+
+    let mut total_distance = 0.;
+    let mut causes = BTreeMap::new();
+    println!("seed,distance,cause,truffles,seconds");
+    for seed in options.first_seed..options.first_seed + options.runs {
+        let mut simulation = Simulation::new(seed);
+        simulation.enable_bot();
+        simulation.start_run();
+        let mut frames = 0;
+        while simulation.is_running() && frames as f32 * FRAME.as_secs_f32() < options.max_seconds {
+            simulation.step();
+            frames += 1;
+        }
+        let cause = simulation
+            .death_cause()
+            .map_or("Timeout".to_string(), |cause| format!("{cause:?}"));
+        println!(
+            "{seed},{:.1},{cause},{},{:.1}",
+            simulation.distance(),
+            simulation.score(),
+            frames as f32 * FRAME.as_secs_f32()
+        );
+        total_distance += simulation.distance();
+        *causes.entry(cause).or_insert(0) += 1;
+    }
+
+    eprintln!(
+        "Average distance over {} runs: {:.1}",
+        options.runs,
+        total_distance / options.runs.max(1) as f32
+    );
+    for (cause, count) in causes {
+        eprintln!("{cause}: {count}");
+    }
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        runs: 100,
+        first_seed: 0,
+        max_seconds: 600.,
+    };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {arg}"))?;
+        let invalid = || format!("Invalid value for {arg}: {value}");
+        match arg.as_str() {
+            "--runs" => options.runs = value.parse().map_err(|_| invalid())?,
+            "--first-seed" => options.first_seed = value.parse().map_err(|_| invalid())?,
+            "--max-seconds" => options.max_seconds = value.parse().map_err(|_| invalid())?,
+            _ => return Err(format!("Unknown argument {arg}")),
+        }
+    }
+    Ok(options)
+}
@@ -0,0 +1,79 @@
+use crate::actions::Actions;
+use crate::effects::Bird;
+use crate::food::{Food, Truffle};
+use crate::map::{Collider, Solid};
+use crate::physics::PhysicsSystems;
+use crate::player::{Grounded, Player};
+use crate::GameState;
+use bevy::math::Vec3Swizzles;
+use bevy::prelude::*;
+
+/// Birds closer than this are avoided
+const BIRD_DISTANCE: f32 = 150.;
+/// How far ahead and above food is jumped for
+const FOOD_REACH: Vec2 = Vec2::new(100., 200.);
+
+pub struct BotPlugin;
+
+/// This plugin lets a bot fill in the [`Actions`] instead of the player
+/// It looks just ahead of the pig for missing ground, walls, food above and birds
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            drive_bot
+                .before(PhysicsSystems::CalculateVelocities)
+                .in_set(OnUpdate(GameState::Playing))
+                .run_if(resource_exists::<Bot>()),
+        );
+    }
+}
+
+/// Present while the bot is playing
+#[derive(Resource, Default)]
+pub struct Bot;
+
+fn drive_bot(
+    mut actions: ResMut<Actions>,
+    player: Query<(&Transform, &Collider, Option<&Grounded>), With<Player>>,
+    solids: Query<(&Transform, &Collider), (With<Solid>, Without<Player>)>,
+    food: Query<&Transform, Or<(With<Food>, With<Truffle>)>>,
+    birds: Query<&Transform, With<Bird>>,
+) {
+    let Ok((transform, collider, grounded)) = player.get_single() else {
+        return;
+    };
+    let position = transform.translation.xy();
+    let solid_at = |point: Vec2| {
+        solids.iter().any(|(transform, collider)| {
+            Rect::from_center_size(transform.translation.xy(), collider.size).contains(point)
+        })
+    };
+
+    // run away from close birds, otherwise keep going right
+    let direction = birds
+        .iter()
+        .map(|bird| bird.translation.xy())
+        .filter(|bird| bird.distance(position) < BIRD_DISTANCE)
+        .min_by(|a, b| {
+            a.distance(position)
+                .partial_cmp(&b.distance(position))
+                .unwrap()
+        })
+        .map_or(1., |bird| if bird.x > position.x { -1. } else { 1. });
+    let front = position.x + direction * collider.size.x / 2.;
+    let bottom = position.y - collider.size.y / 2.;
+
+    let gap_ahead = !solid_at(Vec2::new(front + direction * 4., bottom - 4.));
+    let wall_ahead = solid_at(Vec2::new(front + direction * 8., position.y));
+    let food_above = food.iter().any(|food| {
+        let offset = food.translation.xy() - position;
+        offset.x * direction >= 0.
+            && offset.x.abs() < FOOD_REACH.x
+            && offset.y > collider.size.y
+            && offset.y < FOOD_REACH.y
+    });
+
+    actions.player_movement = direction;
+    actions.attempt_jump = grounded.is_some() && (gap_ahead || wall_ahead || food_above);
+    actions.attempt_dig = false;
+}
@@ -1,8 +1,13 @@
-use crate::map::{MovingControls, CHUNK_WIDTH};
+use crate::map::{
+    MovingControls, StartChunk, CHUNK_WIDTH, LEVEL_HEIGHT, PLAYER_START_X, TILE_SIZE,
+};
 use crate::physics::PhysicsSystems;
 use crate::player::Player;
-use crate::{GameState, HEIGHT, WIDTH};
+use crate::GameState;
+use bevy::math::Vec3Swizzles;
 use bevy::prelude::*;
+use bevy::render::camera::ScalingMode;
+use bevy::window::{PrimaryWindow, WindowResized};
 use bevy_parallax::{
     LayerData, LayerSpeed, ParallaxMoveEvent, ParallaxPlugin, ParallaxResource, ParallaxSystems,
 };
@@ -18,54 +23,79 @@ impl Plugin for CameraPlugin {
                     .after(PhysicsSystems::Move)
                     .before(ParallaxSystems),
             )
+            .add_system(scale_ui.on_startup())
+            .add_system(scale_ui.run_if(on_event::<WindowResized>()))
             .insert_resource(ParallaxResource {
                 layer_data: vec![
-                    LayerData {
-                        speed: LayerSpeed::Horizontal(0.9),
-                        path: "textures/back.png".to_string(),
-                        tile_size: Vec2::new(1024.0, 600.0),
-                        position: Vec2::new(WIDTH / 2., HEIGHT / 2.),
-                        z: 1.0,
-                        ..Default::default()
-                    },
-                    LayerData {
-                        speed: LayerSpeed::Horizontal(0.7),
-                        path: "textures/middle.png".to_string(),
-                        tile_size: Vec2::new(1024.0, 600.0),
-                        position: Vec2::new(WIDTH / 2., HEIGHT / 2.),
-                        z: 2.0,
-                        ..Default::default()
-                    },
-                    LayerData {
-                        speed: LayerSpeed::Horizontal(0.4),
-                        path: "textures/front.png".to_string(),
-                        tile_size: Vec2::new(1024.0, 600.0),
-                        position: Vec2::new(WIDTH / 2., HEIGHT / 2.),
-                        z: 3.0,
-                        ..Default::default()
-                    },
-                    LayerData {
-                        speed: LayerSpeed::Horizontal(0.1),
-                        path: "textures/very_front.png".to_string(),
-                        tile_size: Vec2::new(1024.0, 600.0),
-                        position: Vec2::new(WIDTH / 2., HEIGHT / 2.),
-                        z: 4.0,
-                        ..Default::default()
-                    },
+                    layer("textures/back.png", Vec2::new(0.9, 1.), 1.),
+                    layer("textures/middle.png", Vec2::new(0.7, 0.97), 2.),
+                    layer("textures/front.png", Vec2::new(0.4, 0.94), 3.),
+                    layer("textures/very_front.png", Vec2::new(0.1, 0.9), 4.),
                 ],
                 ..Default::default()
             });
     }
 }
 
-const THRESHOLD: f32 = 0.;
+/// How far ahead of the player the camera looks in the direction they face
+const LOOK_AHEAD: f32 = 100.;
+/// The camera only moves up or down once the player is this far from its center
+const VERTICAL_DEAD_ZONE: f32 = 100.;
+/// Highest the camera goes above its starting height
+const MAX_CAMERA_RISE: f32 = 3. * TILE_SIZE;
+/// Higher values make the camera catch up faster
+const SMOOTHING: f32 = 6.;
+
+/// A background layer that does not show its edges anywhere within the camera bounds
+fn layer(path: &str, speed: Vec2, z: f32) -> LayerData {
+    LayerData {
+        speed: LayerSpeed::Bidirectional(speed.x, speed.y),
+        path: path.to_string(),
+        tile_size: Vec2::new(1024.0, 600.0),
+        // start higher by as much as the layer falls behind the highest camera position
+        position: Vec2::new(
+            PLAYER_START_X,
+            LEVEL_HEIGHT / 2. + (1. - speed.y) * MAX_CAMERA_RISE,
+        ),
+        z,
+        ..Default::default()
+    }
+}
 
 #[derive(Component)]
 pub struct GameCamera;
 
-fn follow_player(
-    player: Query<&Transform, With<Player>>,
-    camera: Query<&Transform, (With<GameCamera>, Without<Player>)>,
+/// A camera that always shows the full height of the level
+///
+/// Wider windows see more of the level to the sides instead of scaling it differently
+pub fn game_camera_bundle() -> Camera2dBundle {
+    let default = Camera2dBundle::default();
+    Camera2dBundle {
+        transform: Transform::from_translation(Vec3::new(
+            PLAYER_START_X,
+            LEVEL_HEIGHT / 2.,
+            default.transform.translation.z,
+        )),
+        projection: OrthographicProjection {
+            scaling_mode: ScalingMode::FixedVertical(LEVEL_HEIGHT),
+            ..default.projection
+        },
+        ..default
+    }
+}
+
+/// Scale the UI with the window height, like the level
+fn scale_ui(window: Query<&Window, With<PrimaryWindow>>, mut ui_scale: ResMut<UiScale>) {
+    if let Ok(window) = window.get_single() {
+        ui_scale.scale = (window.height() / LEVEL_HEIGHT) as f64;
+    }
+}
+
+pub fn follow_player(
+    time: Res<Time>,
+    start_chunk: Res<StartChunk>,
+    player: Query<(&Transform, &TextureAtlasSprite), With<Player>>,
+    camera: Query<(&Transform, &OrthographicProjection), (With<GameCamera>, Without<Player>)>,
     mut move_event_writer: EventWriter<ParallaxMoveEvent>,
     mut commands: Commands,
     mut controls: Query<
@@ -73,25 +103,38 @@ fn follow_player(
         (With<MovingControls>, Without<Player>, Without<GameCamera>),
     >,
 ) {
-    let camera_transform = camera.single();
-    let delta = player.single().translation.x - camera_transform.translation.x;
-    if delta.abs() > THRESHOLD {
-        let mut move_by = if delta > 0. {
-            delta - THRESHOLD
-        } else {
-            delta + THRESHOLD
-        };
-        if camera_transform.translation.x + move_by < WIDTH / 2. {
-            move_by = (WIDTH / 2.) - camera_transform.translation.x;
-        }
-        move_event_writer.send(ParallaxMoveEvent {
-            camera_move_speed: Vec2::new(move_by, 0.0),
-        });
-        for (control, mut control_transform) in &mut controls {
-            control_transform.translation.x += move_by;
-            if control_transform.translation.x > 4. * CHUNK_WIDTH {
-                commands.entity(control).despawn();
-            }
+    let (camera_transform, projection) = camera.single();
+    let camera = camera_transform.translation.xy();
+    let half_view_width = projection.area.width() / projection.scale / 2.;
+    let (player_transform, sprite) = player.single();
+    let player = player_transform.translation.xy();
+
+    let direction = if sprite.flip_x { -1. } else { 1. };
+    let mut target = Vec2::new(player.x + direction * LOOK_AHEAD, camera.y);
+    if player.y > camera.y + VERTICAL_DEAD_ZONE {
+        target.y = player.y - VERTICAL_DEAD_ZONE;
+    } else if player.y < camera.y - VERTICAL_DEAD_ZONE {
+        target.y = player.y + VERTICAL_DEAD_ZONE;
+    }
+    let min = Vec2::new(start_chunk.left_edge() + half_view_width, LEVEL_HEIGHT / 2.);
+    target = target.clamp(min, min + Vec2::new(f32::INFINITY, MAX_CAMERA_RISE));
+
+    // jump right to the player after they were moved to the start of a run
+    let move_by = if target.distance(camera) > CHUNK_WIDTH {
+        target - camera
+    } else {
+        (target - camera) * (1. - (-SMOOTHING * time.delta_seconds()).exp())
+    };
+    if move_by.length() < f32::EPSILON {
+        return;
+    }
+    move_event_writer.send(ParallaxMoveEvent {
+        camera_move_speed: move_by,
+    });
+    for (control, mut control_transform) in &mut controls {
+        control_transform.translation += move_by.extend(0.);
+        if control_transform.translation.x > 4. * CHUNK_WIDTH {
+            commands.entity(control).despawn();
         }
     }
 }
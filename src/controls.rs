@@ -0,0 +1,271 @@
+use crate::actions::{Binding, ControlInput, GameControl, InputMap};
+use crate::loading::FontAssets;
+use crate::menu::{spawn_overlay, ButtonColors, OVERLAY_Z};
+use crate::settings::SettingsMenuState;
+use bevy::prelude::*;
+
+pub struct ControlsPlugin;
+
+/// This plugin is responsible for the key binding screen reachable from the settings
+/// Rebinding waits for the next key, mouse or gamepad button and refuses bindings used elsewhere
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rebinding>()
+            .add_system(setup_controls_menu.in_schedule(OnEnter(SettingsMenuState::Controls)))
+            .add_systems(
+                (
+                    close_on_back,
+                    capture_binding,
+                    click_controls_buttons,
+                    update_binding_texts,
+                )
+                    .chain()
+                    .in_set(OnUpdate(SettingsMenuState::Controls)),
+            )
+            .add_system(cleanup_controls_menu.in_schedule(OnExit(SettingsMenuState::Controls)));
+    }
+}
+
+/// The control currently waiting for a new binding
+#[derive(Resource, Default)]
+struct Rebinding(Option<GameControl>);
+
+#[derive(Component)]
+struct ControlsMenu;
+
+#[derive(Component, Clone, Copy)]
+enum ControlsButton {
+    Add(GameControl),
+    Clear(GameControl),
+    Defaults,
+    Back,
+}
+
+#[derive(Component)]
+struct BindingsText(GameControl);
+
+#[derive(Component)]
+struct StatusText;
+
+fn bindings_text(input_map: &InputMap, control: GameControl) -> String {
+    let bindings = input_map.bindings(control);
+    if bindings.is_empty() {
+        return "-".to_string();
+    }
+    bindings
+        .iter()
+        .map(Binding::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn setup_controls_menu(
+    mut commands: Commands,
+    font_assets: Res<FontAssets>,
+    button_colors: Res<ButtonColors>,
+    input_map: Res<InputMap>,
+) {
+    let text_style = TextStyle {
+        font: font_assets.fira_sans.clone(),
+        font_size: 30.0,
+        color: Color::rgb(0.9, 0.9, 0.9),
+    };
+    let small_text_style = TextStyle {
+        font_size: 18.0,
+        ..text_style.clone()
+    };
+    let button_style = Style {
+        size: Size::new(Val::Px(70.0), Val::Px(40.0)),
+        margin: UiRect::horizontal(Val::Px(5.)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..Default::default()
+    };
+    spawn_overlay(&mut commands, OVERLAY_Z)
+        .insert(ControlsMenu)
+        .with_children(|parent| {
+            for control in GameControl::ALL {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(700.0), Val::Px(50.0)),
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(
+                            TextBundle::from_section(control.label(), text_style.clone())
+                                .with_style(Style {
+                                    size: Size::new(Val::Px(100.0), Val::Auto),
+                                    ..Default::default()
+                                }),
+                        );
+                        parent
+                            .spawn(
+                                TextBundle::from_section(
+                                    bindings_text(&input_map, control),
+                                    small_text_style.clone(),
+                                )
+                                .with_style(Style {
+                                    size: Size::new(Val::Px(430.0), Val::Auto),
+                                    ..Default::default()
+                                }),
+                            )
+                            .insert(BindingsText(control));
+                        for (button, label) in [
+                            (ControlsButton::Add(control), "Add"),
+                            (ControlsButton::Clear(control), "Clear"),
+                        ] {
+                            parent
+                                .spawn(ButtonBundle {
+                                    style: button_style.clone(),
+                                    background_color: button_colors.normal.into(),
+                                    ..Default::default()
+                                })
+                                .insert(button)
+                                .with_children(|parent| {
+                                    parent.spawn(TextBundle::from_section(
+                                        label,
+                                        small_text_style.clone(),
+                                    ));
+                                });
+                        }
+                    });
+            }
+            parent
+                .spawn(
+                    TextBundle::from_section("", small_text_style.clone()).with_style(Style {
+                        margin: UiRect::vertical(Val::Px(10.)),
+                        ..Default::default()
+                    }),
+                )
+                .insert(StatusText);
+            parent.spawn(NodeBundle::default()).with_children(|parent| {
+                for (button, label) in [
+                    (ControlsButton::Defaults, "Defaults"),
+                    (ControlsButton::Back, "Back"),
+                ] {
+                    parent
+                        .spawn(ButtonBundle {
+                            style: Style {
+                                size: Size::new(Val::Px(150.0), Val::Px(50.0)),
+                                margin: UiRect::horizontal(Val::Px(10.)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..Default::default()
+                            },
+                            background_color: button_colors.normal.into(),
+                            ..Default::default()
+                        })
+                        .insert(button)
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                        });
+                }
+            });
+        });
+}
+
+fn close_on_back(
+    control_input: ControlInput,
+    rebinding: Res<Rebinding>,
+    mut state: ResMut<NextState<SettingsMenuState>>,
+) {
+    if rebinding.0.is_none() && control_input.just_pressed(GameControl::Back) {
+        state.set(SettingsMenuState::Open);
+    }
+}
+
+/// Runs before the buttons are handled, so the click that started rebinding is not captured
+fn capture_binding(
+    mut rebinding: ResMut<Rebinding>,
+    mut input_map: ResMut<InputMap>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    mut status: Query<&mut Text, With<StatusText>>,
+) {
+    let Some(control) = rebinding.0 else {
+        return;
+    };
+    let mut status = status.single_mut();
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        rebinding.0 = None;
+        status.sections[0].value = String::new();
+        return;
+    }
+    let binding = keyboard_input
+        .get_just_pressed()
+        .next()
+        .map(|key| Binding::Key(*key))
+        .or_else(|| {
+            mouse_input
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::Mouse(*button))
+        })
+        .or_else(|| {
+            gamepad_input
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::Gamepad(button.button_type))
+        });
+    let Some(binding) = binding else {
+        return;
+    };
+    rebinding.0 = None;
+    status.sections[0].value = match input_map.bind(control, binding) {
+        Err(bound) if bound != control => {
+            format!("{binding} is already bound to {}", bound.label())
+        }
+        _ => String::new(),
+    };
+}
+
+fn click_controls_buttons(
+    mut input_map: ResMut<InputMap>,
+    mut rebinding: ResMut<Rebinding>,
+    mut state: ResMut<NextState<SettingsMenuState>>,
+    mut status: Query<&mut Text, With<StatusText>>,
+    interaction_query: Query<(&Interaction, &ControlsButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        match *button {
+            ControlsButton::Add(control) => {
+                rebinding.0 = Some(control);
+                status.single_mut().sections[0].value =
+                    format!("Press a key for {}... (Esc to cancel)", control.label());
+            }
+            ControlsButton::Clear(control) => input_map.clear(control),
+            ControlsButton::Defaults => *input_map = InputMap::default(),
+            ControlsButton::Back => state.set(SettingsMenuState::Open),
+        }
+    }
+}
+
+fn update_binding_texts(input_map: Res<InputMap>, mut texts: Query<(&mut Text, &BindingsText)>) {
+    if !input_map.is_changed() {
+        return;
+    }
+    for (mut text, control) in &mut texts {
+        text.sections[0].value = bindings_text(&input_map, control.0);
+    }
+}
+
+fn cleanup_controls_menu(
+    mut commands: Commands,
+    input_map: Res<InputMap>,
+    mut rebinding: ResMut<Rebinding>,
+    menu: Query<Entity, With<ControlsMenu>>,
+) {
+    rebinding.0 = None;
+    input_map.save();
+    for entity in &menu {
+        commands.entity(entity).despawn_recursive();
+    }
+}
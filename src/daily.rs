@@ -0,0 +1,381 @@
+use crate::actions::{ControlInput, GameControl};
+use crate::loading::FontAssets;
+use crate::map::TILE_SIZE;
+use crate::menu::{spawn_overlay, ButtonColors, OVERLAY_Z};
+use crate::player::Player;
+use crate::replay::{stop_replay, Replay};
+use crate::seed::{reseed, SeedOverride};
+use crate::settings::{load_config, save_config};
+use crate::ui::Score;
+use crate::GameState;
+use bevy::prelude::*;
+use instant::SystemTime;
+use serde::{Deserialize, Serialize};
+
+const DAILY_FILE: &str = "daily.ron";
+const LEADERBOARD_SIZE: usize = 10;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+/// Keeps daily seeds apart from small seeds picked by hand
+const DAILY_SEED_SALT: u64 = 0xDA11_C4A1_1E46_E000;
+
+pub struct DailyPlugin;
+
+/// This plugin adds the daily challenge, a course that is the same for everyone on a given UTC day
+/// Only the first run of a day is scored, every following one is practice
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<DailyMenuState>()
+            .insert_resource(DailyRecords::load())
+            .add_system(setup_daily_menu.in_schedule(OnEnter(DailyMenuState::Open)))
+            .add_systems(
+                (close_on_back, click_daily_buttons).in_set(OnUpdate(DailyMenuState::Open)),
+            )
+            .add_system(cleanup_daily_menu.in_schedule(OnExit(DailyMenuState::Open)))
+            .add_system(
+                start_daily_run
+                    .after(reseed)
+                    .in_schedule(OnEnter(GameState::Prepare)),
+            )
+            .add_system(
+                start_daily_run
+                    .after(reseed)
+                    .in_schedule(OnExit(GameState::Restart)),
+            )
+            .add_system(
+                track_daily_run
+                    .in_set(OnUpdate(GameState::Playing))
+                    .run_if(resource_exists::<DailyRun>()),
+            )
+            .add_system(finish_daily_run.in_schedule(OnEnter(GameState::Restart)))
+            .add_systems(
+                (
+                    finish_daily_run,
+                    leave_daily.after(finish_daily_run).after(stop_replay),
+                )
+                    .in_schedule(OnEnter(GameState::Menu)),
+            );
+    }
+}
+
+#[derive(States, Default, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum DailyMenuState {
+    #[default]
+    Closed,
+    Open,
+}
+
+/// Days since the unix epoch in UTC
+fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() / SECONDS_PER_DAY)
+}
+
+fn daily_seed(day: u64) -> u64 {
+    DAILY_SEED_SALT ^ day
+}
+
+/// Formats days since the unix epoch as `YYYY-MM-DD`
+fn format_day(day: u64) -> String {
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = day + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day_of_month = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day_of_month:02}")
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct DailyEntry {
+    day: u64,
+    score: f32,
+    distance: f32,
+}
+
+/// The local daily leaderboard, persisted in the config directory
+#[derive(Resource, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct DailyRecords {
+    last_scored_day: Option<u64>,
+    leaderboard: Vec<DailyEntry>,
+}
+
+impl DailyRecords {
+    fn load() -> Self {
+        load_config(DAILY_FILE)
+    }
+
+    fn save(&self) {
+        save_config(DAILY_FILE, self)
+    }
+
+    fn scored_today(&self) -> bool {
+        self.last_scored_day == Some(today())
+    }
+
+    fn insert(&mut self, entry: DailyEntry) {
+        self.leaderboard.push(entry);
+        self.leaderboard.sort_by(|a, b| {
+            (b.score, b.distance)
+                .partial_cmp(&(a.score, a.distance))
+                .unwrap()
+        });
+        self.leaderboard.truncate(LEADERBOARD_SIZE);
+    }
+}
+
+/// Present while runs use the daily seed
+#[derive(Resource)]
+struct DailyRun {
+    day: u64,
+    /// The current run is the one attempt of the day that counts
+    scored: bool,
+    score: f32,
+    distance: f32,
+    previous_seed: Option<u64>,
+}
+
+#[derive(Component)]
+struct DailyMenu;
+
+#[derive(Component)]
+struct DailyText;
+
+#[derive(Component, Clone, Copy)]
+enum DailyButton {
+    Play,
+    Back,
+}
+
+fn setup_daily_menu(
+    mut commands: Commands,
+    font_assets: Res<FontAssets>,
+    button_colors: Res<ButtonColors>,
+    records: Res<DailyRecords>,
+) {
+    let text_style = TextStyle {
+        font: font_assets.fira_sans.clone(),
+        font_size: 30.0,
+        color: Color::rgb(0.9, 0.9, 0.9),
+    };
+    let small_text_style = TextStyle {
+        font_size: 22.0,
+        ..text_style.clone()
+    };
+    let scored_today = records.scored_today();
+    let mut leaderboard: Vec<String> = records
+        .leaderboard
+        .iter()
+        .enumerate()
+        .map(|(place, entry)| {
+            format!(
+                "{}. {}   {:.0}   {:.0}m",
+                place + 1,
+                format_day(entry.day),
+                entry.score,
+                entry.distance
+            )
+        })
+        .collect();
+    if leaderboard.is_empty() {
+        leaderboard.push("No daily runs yet".to_string());
+    }
+    spawn_overlay(&mut commands, OVERLAY_Z)
+        .insert(DailyMenu)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!("Daily challenge {}", format_day(today())),
+                text_style.clone(),
+            ));
+            parent.spawn(
+                TextBundle::from_section(
+                    if scored_today {
+                        "Already played today, practice only"
+                    } else {
+                        "Your first run today is scored"
+                    },
+                    small_text_style.clone(),
+                )
+                .with_style(Style {
+                    margin: UiRect::vertical(Val::Px(10.)),
+                    ..Default::default()
+                }),
+            );
+            parent.spawn(
+                TextBundle::from_section(leaderboard.join("\n"), small_text_style.clone())
+                    .with_style(Style {
+                        margin: UiRect::vertical(Val::Px(10.)),
+                        ..Default::default()
+                    }),
+            );
+            parent.spawn(NodeBundle::default()).with_children(|parent| {
+                for (button, label) in [
+                    (
+                        DailyButton::Play,
+                        if scored_today { "Practice" } else { "Play" },
+                    ),
+                    (DailyButton::Back, "Back"),
+                ] {
+                    parent
+                        .spawn(ButtonBundle {
+                            style: Style {
+                                size: Size::new(Val::Px(150.0), Val::Px(50.0)),
+                                margin: UiRect::horizontal(Val::Px(10.)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..Default::default()
+                            },
+                            background_color: button_colors.normal.into(),
+                            ..Default::default()
+                        })
+                        .insert(button)
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                        });
+                }
+            });
+        });
+}
+
+fn close_on_back(control_input: ControlInput, mut state: ResMut<NextState<DailyMenuState>>) {
+    if control_input.just_pressed(GameControl::Back) {
+        state.set(DailyMenuState::Closed);
+    }
+}
+
+fn click_daily_buttons(
+    mut commands: Commands,
+    mut seed_override: ResMut<SeedOverride>,
+    mut state: ResMut<NextState<GameState>>,
+    mut daily_state: ResMut<NextState<DailyMenuState>>,
+    interaction_query: Query<(&Interaction, &DailyButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        if let DailyButton::Play = button {
+            let day = today();
+            commands.insert_resource(DailyRun {
+                day,
+                scored: false,
+                score: 0.,
+                distance: 0.,
+                previous_seed: seed_override.0.replace(daily_seed(day)),
+            });
+            state.set(GameState::Prepare);
+        }
+        daily_state.set(DailyMenuState::Closed);
+    }
+}
+
+fn cleanup_daily_menu(mut commands: Commands, menu: Query<Entity, With<DailyMenu>>) {
+    for entity in &menu {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn start_daily_run(
+    mut commands: Commands,
+    daily_run: Option<ResMut<DailyRun>>,
+    mut records: ResMut<DailyRecords>,
+    replay: Option<Res<Replay>>,
+    font_assets: Res<FontAssets>,
+    text: Query<Entity, With<DailyText>>,
+) {
+    let Some(mut daily_run) = daily_run else {
+        return;
+    };
+    daily_run.scored = replay.is_none() && records.last_scored_day != Some(daily_run.day);
+    daily_run.score = 0.;
+    daily_run.distance = 0.;
+    if daily_run.scored {
+        // quitting the scored attempt still uses it up
+        records.last_scored_day = Some(daily_run.day);
+        records.save();
+    }
+
+    for entity in &text {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands
+        .spawn(
+            TextBundle::from_section(
+                if daily_run.scored {
+                    format!("Daily {}", format_day(daily_run.day))
+                } else {
+                    "Daily practice".to_string()
+                },
+                TextStyle {
+                    font: font_assets.fira_sans.clone(),
+                    font_size: 30.0,
+                    color: Color::rgb_u8(34, 32, 52),
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(10.),
+                    top: Val::Px(70.),
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(DailyText);
+}
+
+fn track_daily_run(
+    mut daily_run: ResMut<DailyRun>,
+    score: Res<Score>,
+    player: Query<&Transform, With<Player>>,
+) {
+    daily_run.score = score.0;
+    if let Ok(transform) = player.get_single() {
+        daily_run.distance = daily_run.distance.max(transform.translation.x / TILE_SIZE);
+    }
+}
+
+/// Also runs when the scored attempt is restarted or quit from the pause menu
+/// It was used up when it started, so it always gets its leaderboard entry
+fn finish_daily_run(daily_run: Option<ResMut<DailyRun>>, mut records: ResMut<DailyRecords>) {
+    let Some(mut daily_run) = daily_run else {
+        return;
+    };
+    if !daily_run.scored {
+        return;
+    }
+    daily_run.scored = false;
+    records.insert(DailyEntry {
+        day: daily_run.day,
+        score: daily_run.score,
+        distance: daily_run.distance,
+    });
+    records.save();
+}
+
+fn leave_daily(
+    mut commands: Commands,
+    daily_run: Option<Res<DailyRun>>,
+    mut seed_override: ResMut<SeedOverride>,
+    text: Query<Entity, With<DailyText>>,
+) {
+    let Some(daily_run) = daily_run else {
+        return;
+    };
+    seed_override.0 = daily_run.previous_seed;
+    commands.remove_resource::<DailyRun>();
+    for entity in &text {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,292 @@
+use crate::camera::GameCamera;
+use crate::effects::{Bird, CurrentEffects};
+use crate::food::{Food, Truffle};
+use crate::loading::FontAssets;
+use crate::map::{Collider, CurrentChunk, Solid, CHUNK_WIDTH, LEVEL_HEIGHT};
+use crate::physics::Velocity;
+use crate::player::{Grounded, Player};
+use crate::GameState;
+use bevy::diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin};
+use bevy::ecs::query::WorldQuery;
+use bevy::math::Vec3Swizzles;
+use bevy::prelude::*;
+use bevy_editor_pls::controls::{self, EditorControls};
+use bevy_editor_pls::prelude::*;
+
+/// Above the level and the player, but below the menu backgrounds
+const DEBUG_Z: f32 = 50.;
+const LINE_WIDTH: f32 = 2.;
+/// Velocity arrows show how far the entity moves in this time
+const VELOCITY_SECONDS: f32 = 0.1;
+
+pub struct DebugPlugin;
+
+/// This plugin adds the tools used while working on the game, it is only built with `debug-tools`
+/// F1 toggles the editor, F2 the collision overlay, F3 the frame rate and R ends the current run
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugOverlays>()
+            .insert_resource(editor_controls())
+            .add_plugin(EditorPlugin::new())
+            .add_plugin(FrameTimeDiagnosticsPlugin::default())
+            .add_system(toggle_overlays)
+            .add_system(reset.run_if(in_state(GameState::Playing)))
+            .add_system(draw_shapes.after(toggle_overlays))
+            .add_systems(
+                (show_texts, update_fps, update_state_text)
+                    .chain()
+                    .after(toggle_overlays)
+                    .distributive_run_if(resource_exists::<FontAssets>()),
+            );
+    }
+}
+
+/// Debug overlays that are currently shown
+#[derive(Resource, Default)]
+pub struct DebugOverlays {
+    pub fps: bool,
+    pub colliders: bool,
+}
+
+#[derive(Component)]
+struct FpsText;
+
+#[derive(Component)]
+struct StateText;
+
+/// A line of the collision overlay, kept hidden for reuse while it is not needed
+#[derive(Component)]
+struct DebugShape;
+
+#[derive(WorldQuery)]
+struct ColliderQuery {
+    transform: &'static Transform,
+    collider: &'static Collider,
+    solid: Option<&'static Solid>,
+    food: Option<&'static Food>,
+    truffle: Option<&'static Truffle>,
+    bird: Option<&'static Bird>,
+    player: Option<&'static Player>,
+}
+
+impl ColliderQueryItem<'_> {
+    fn color(&self) -> Color {
+        if self.player.is_some() {
+            Color::BLUE
+        } else if self.bird.is_some() {
+            Color::RED
+        } else if self.truffle.is_some() {
+            Color::YELLOW
+        } else if self.food.is_some() {
+            Color::ORANGE
+        } else if self.solid.is_some() {
+            Color::GREEN
+        } else {
+            Color::WHITE
+        }
+    }
+}
+
+fn editor_controls() -> EditorControls {
+    let mut editor_controls = EditorControls::default_bindings();
+    editor_controls.unbind(controls::Action::PlayPauseEditor);
+    editor_controls.insert(
+        controls::Action::PlayPauseEditor,
+        controls::Binding {
+            input: controls::UserInput::Single(controls::Button::Keyboard(KeyCode::F1)),
+            conditions: vec![controls::BindingCondition::ListeningForText(false)],
+        },
+    );
+    editor_controls
+}
+
+fn toggle_overlays(input: Res<Input<KeyCode>>, mut overlays: ResMut<DebugOverlays>) {
+    if input.just_pressed(KeyCode::F2) {
+        overlays.colliders = !overlays.colliders;
+    }
+    if input.just_pressed(KeyCode::F3) {
+        overlays.fps = !overlays.fps;
+    }
+}
+
+fn reset(mut state: ResMut<NextState<GameState>>, input: Res<Input<KeyCode>>) {
+    if input.just_pressed(KeyCode::R) {
+        state.set(GameState::Restart);
+    }
+}
+
+fn draw_shapes(
+    mut commands: Commands,
+    overlays: Res<DebugOverlays>,
+    mut shapes: Query<(&mut Sprite, &mut Transform, &mut Visibility), With<DebugShape>>,
+    colliders: Query<ColliderQuery, Without<DebugShape>>,
+    player: Query<(&Transform, &Velocity), (With<Player>, Without<DebugShape>)>,
+    camera: Query<(&Transform, &OrthographicProjection), (With<GameCamera>, Without<DebugShape>)>,
+) {
+    let mut lines = Vec::new();
+    if let (true, Ok((camera, projection))) = (overlays.colliders, camera.get_single()) {
+        let center = camera.translation.xy();
+        let view = Rect::from_corners(
+            center + projection.area.min * camera.scale.xy(),
+            center + projection.area.max * camera.scale.xy(),
+        );
+        let mut line = |start: Vec2, end: Vec2, color: Color| lines.push((start, end, color));
+
+        for item in &colliders {
+            let rect = Rect::from_center_size(item.transform.translation.xy(), item.collider.size);
+            if view.intersect(rect).is_empty() {
+                continue;
+            }
+            let color = item.color();
+            line(rect.min, Vec2::new(rect.max.x, rect.min.y), color);
+            line(Vec2::new(rect.max.x, rect.min.y), rect.max, color);
+            line(rect.max, Vec2::new(rect.min.x, rect.max.y), color);
+            line(Vec2::new(rect.min.x, rect.max.y), rect.min, color);
+        }
+        if let Ok((transform, velocity)) = player.get_single() {
+            let position = transform.translation.xy();
+            line(
+                position,
+                position + velocity.0 * VELOCITY_SECONDS,
+                Color::CYAN,
+            );
+        }
+        let first = (view.min.x / CHUNK_WIDTH).floor().max(0.) as usize;
+        let last = (view.max.x / CHUNK_WIDTH).ceil() as usize;
+        for chunk in first..=last {
+            let x = chunk as f32 * CHUNK_WIDTH;
+            line(
+                Vec2::new(x, -LEVEL_HEIGHT),
+                Vec2::new(x, 2. * LEVEL_HEIGHT),
+                Color::FUCHSIA,
+            );
+        }
+    }
+
+    // reuse the line sprites from earlier frames and only spawn the ones still missing
+    let mut lines = lines.into_iter();
+    for (mut sprite, mut transform, mut visibility) in &mut shapes {
+        let Some((start, end, color)) = lines.next() else {
+            if *visibility != Visibility::Hidden {
+                *visibility = Visibility::Hidden;
+            }
+            continue;
+        };
+        (*sprite, *transform) = line_sprite(start, end, color);
+        *visibility = Visibility::Inherited;
+    }
+    for (start, end, color) in lines {
+        let (sprite, transform) = line_sprite(start, end, color);
+        commands.spawn((
+            SpriteBundle {
+                sprite,
+                transform,
+                ..default()
+            },
+            DebugShape,
+        ));
+    }
+}
+
+fn line_sprite(start: Vec2, end: Vec2, color: Color) -> (Sprite, Transform) {
+    let direction = end - start;
+    let sprite = Sprite {
+        color,
+        custom_size: Some(Vec2::new(direction.length() + LINE_WIDTH, LINE_WIDTH)),
+        ..default()
+    };
+    let transform = Transform::from_translation(((start + end) / 2.).extend(DEBUG_Z))
+        .with_rotation(Quat::from_rotation_z(direction.y.atan2(direction.x)));
+    (sprite, transform)
+}
+
+fn overlay_text(font_assets: &FontAssets, position: UiRect) -> TextBundle {
+    TextBundle::from_section(
+        "",
+        TextStyle {
+            font: font_assets.fira_sans.clone(),
+            font_size: 20.0,
+            color: Color::rgb_u8(34, 32, 52),
+        },
+    )
+    .with_style(Style {
+        position_type: PositionType::Absolute,
+        position,
+        ..default()
+    })
+}
+
+fn show_texts(
+    mut commands: Commands,
+    overlays: Res<DebugOverlays>,
+    font_assets: Res<FontAssets>,
+    texts: Query<Entity, Or<(With<FpsText>, With<StateText>)>>,
+) {
+    if !overlays.is_changed() {
+        return;
+    }
+    for entity in &texts {
+        commands.entity(entity).despawn_recursive();
+    }
+    if overlays.fps {
+        commands
+            .spawn(overlay_text(
+                &font_assets,
+                UiRect {
+                    left: Val::Px(10.),
+                    bottom: Val::Px(10.),
+                    ..default()
+                },
+            ))
+            .insert(ZIndex::Global(100))
+            .insert(FpsText);
+    }
+    if overlays.colliders {
+        commands
+            .spawn(overlay_text(
+                &font_assets,
+                UiRect {
+                    left: Val::Px(10.),
+                    bottom: Val::Px(40.),
+                    ..default()
+                },
+            ))
+            .insert(ZIndex::Global(100))
+            .insert(StateText);
+    }
+}
+
+fn update_fps(diagnostics: Res<Diagnostics>, mut text: Query<&mut Text, With<FpsText>>) {
+    let Some(fps) = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+    else {
+        return;
+    };
+    for mut text in &mut text {
+        text.sections[0].value = format!("{fps:.0} FPS");
+    }
+}
+
+fn update_state_text(
+    time: Res<Time>,
+    current_chunk: Res<CurrentChunk>,
+    current_effects: Res<CurrentEffects>,
+    player: Query<(&Velocity, Option<&Grounded>), With<Player>>,
+    mut text: Query<&mut Text, With<StateText>>,
+) {
+    let mut lines = vec![format!("Chunk {}", current_chunk.0)];
+    if let Ok((velocity, grounded)) = player.get_single() {
+        lines.push(format!("Grounded: {}", grounded.is_some()));
+        lines.push(format!(
+            "Velocity: {:.0}, {:.0}",
+            velocity.0.x, velocity.0.y
+        ));
+    }
+    for (effect, end) in &current_effects.0 {
+        lines.push(format!("{effect:?}: {:.1}s", end - time.elapsed_seconds()));
+    }
+    for mut text in &mut text {
+        text.sections[0].value = lines.join("\n");
+    }
+}
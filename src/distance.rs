@@ -0,0 +1,229 @@
+use crate::loading::FontAssets;
+use crate::map::{Level, Signpost, PLATFORM_HEIGHT, PLATFORM_Z, TILE_SIZE};
+use crate::physics::PhysicsSystems;
+use crate::player::Player;
+use crate::replay::Replay;
+use crate::reset::run_finished;
+use crate::settings::{load_config, save_config};
+use crate::ui::Hud;
+use crate::GameState;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const RECORDS_FILE: &str = "records.ron";
+/// The best distance flag stands behind the level, like the signposts
+const MARKER_Z: f32 = PLATFORM_Z - 1.;
+const POST_HEIGHT: f32 = 70.;
+const POST_COLOR: Color = Color::rgb(0.45, 0.3, 0.2);
+const BOARD_COLOR: Color = Color::rgb(0.85, 0.7, 0.5);
+const FLAG_COLOR: Color = Color::rgb(0.85, 0.2, 0.2);
+const TEXT_COLOR: Color = Color::rgb(34. / 255., 32. / 255., 52. / 255.);
+
+pub struct DistancePlugin;
+
+/// This plugin shows how far the current run got and remembers the best finished run
+/// Signposts spawned by the map get their board here, and a flag marks the previous best
+impl Plugin for DistancePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Records::load())
+            .init_resource::<RunDistance>()
+            .add_system(spawn_distance_text.in_schedule(OnEnter(GameState::Prepare)))
+            .add_system(start_run.in_schedule(OnEnter(GameState::Prepare)))
+            .add_system(start_run.in_schedule(OnExit(GameState::Restart)))
+            .add_systems(
+                (track_distance, update_distance_text)
+                    .chain()
+                    .after(PhysicsSystems::Move)
+                    .in_set(OnUpdate(GameState::Playing)),
+            )
+            .add_system(decorate_signposts.run_if(resource_exists::<FontAssets>()))
+            .add_system(
+                save_best_distance
+                    .run_if(run_finished)
+                    .in_schedule(OnEnter(GameState::Restart)),
+            );
+    }
+}
+
+/// Distance in metres from the left end of the level, a tile is one metre
+pub fn metres(x: f32) -> f32 {
+    x / TILE_SIZE
+}
+
+/// Furthest distance of the current run in metres
+#[derive(Resource, Default)]
+pub struct RunDistance(pub(crate) f32);
+
+#[derive(Resource, Serialize, Deserialize, Default)]
+struct Records {
+    best_distance: f32,
+}
+
+impl Records {
+    fn load() -> Self {
+        load_config(RECORDS_FILE)
+    }
+
+    fn save(&self) {
+        save_config(RECORDS_FILE, self)
+    }
+}
+
+#[derive(Component)]
+struct DistanceText;
+
+fn spawn_distance_text(mut commands: Commands, font_assets: Res<FontAssets>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Auto),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                position: UiRect {
+                    top: Val::Px(10.),
+                    ..default()
+                },
+                ..default()
+            },
+            ..default()
+        })
+        .insert(Hud)
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle::from_section(
+                    "0m",
+                    TextStyle {
+                        font: font_assets.fira_sans.clone(),
+                        font_size: 40.0,
+                        color: TEXT_COLOR,
+                    },
+                ))
+                .insert(DistanceText);
+        });
+}
+
+fn start_run(
+    mut commands: Commands,
+    records: Res<Records>,
+    mut run_distance: ResMut<RunDistance>,
+    font_assets: Res<FontAssets>,
+) {
+    run_distance.0 = 0.;
+    if records.best_distance <= 0. {
+        return;
+    }
+    let x = records.best_distance * TILE_SIZE;
+    commands
+        .spawn(SpatialBundle::from_transform(Transform::from_xyz(
+            x,
+            PLATFORM_HEIGHT,
+            MARKER_Z,
+        )))
+        .insert(Level)
+        .with_children(|parent| {
+            parent.spawn(marker_sprite(
+                POST_COLOR,
+                Vec2::new(4., POST_HEIGHT),
+                Vec2::new(0., POST_HEIGHT / 2.),
+            ));
+            parent.spawn(marker_sprite(
+                FLAG_COLOR,
+                Vec2::new(36., 22.),
+                Vec2::new(20., POST_HEIGHT - 11.),
+            ));
+            parent.spawn(marker_text(
+                &font_assets,
+                "Best".to_string(),
+                Vec2::new(0., POST_HEIGHT + 14.),
+            ));
+        });
+}
+
+fn marker_sprite(color: Color, size: Vec2, position: Vec2) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(size),
+            ..default()
+        },
+        transform: Transform::from_translation(position.extend(0.)),
+        ..default()
+    }
+}
+
+fn marker_text(font_assets: &FontAssets, value: String, position: Vec2) -> Text2dBundle {
+    Text2dBundle {
+        text: Text::from_section(
+            value,
+            TextStyle {
+                font: font_assets.fira_sans.clone(),
+                font_size: 20.0,
+                color: TEXT_COLOR,
+            },
+        ),
+        // in front of the board
+        transform: Transform::from_translation(position.extend(0.1)),
+        ..default()
+    }
+}
+
+fn decorate_signposts(
+    mut commands: Commands,
+    font_assets: Res<FontAssets>,
+    signposts: Query<(Entity, &Signpost), Added<Signpost>>,
+) {
+    for (entity, signpost) in &signposts {
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn(marker_sprite(
+                POST_COLOR,
+                Vec2::new(6., POST_HEIGHT),
+                Vec2::new(0., POST_HEIGHT / 2.),
+            ));
+            parent.spawn(marker_sprite(
+                BOARD_COLOR,
+                Vec2::new(56., 26.),
+                Vec2::new(0., POST_HEIGHT - 13.),
+            ));
+            parent.spawn(marker_text(
+                &font_assets,
+                format!("{}m", signpost.0),
+                Vec2::new(0., POST_HEIGHT - 13.),
+            ));
+        });
+    }
+}
+
+fn track_distance(player: Query<&Transform, With<Player>>, mut run_distance: ResMut<RunDistance>) {
+    let Ok(transform) = player.get_single() else {
+        return;
+    };
+    let distance = metres(transform.translation.x);
+    if distance > run_distance.0 {
+        run_distance.0 = distance;
+    }
+}
+
+fn update_distance_text(
+    run_distance: Res<RunDistance>,
+    mut text: Query<&mut Text, With<DistanceText>>,
+) {
+    if !run_distance.is_changed() {
+        return;
+    }
+    for mut text in &mut text {
+        text.sections[0].value = format!("{:.0}m", run_distance.0);
+    }
+}
+
+fn save_best_distance(
+    run_distance: Res<RunDistance>,
+    mut records: ResMut<Records>,
+    replay: Option<Res<Replay>>,
+) {
+    if replay.is_some() || run_distance.0 <= records.best_distance {
+        return;
+    }
+    info!("New best distance {:.0}m", run_distance.0);
+    records.best_distance = run_distance.0;
+    records.save();
+}
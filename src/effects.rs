@@ -1,8 +1,10 @@
+use crate::audio::{Sound, SoundEvent};
 use crate::loading::TextureAssets;
-use crate::map::{Collider, Level};
+use crate::map::{Collider, Level, LEVEL_HEIGHT};
 use crate::physics::{Move, PhysicsSystems, Velocity};
 use crate::player::{Player, PlayerControls};
-use crate::{GameState, HEIGHT, WIDTH};
+use crate::tuning::Tuning;
+use crate::GameState;
 use bevy::ecs::query::WorldQuery;
 use bevy::ecs::system::Command;
 use bevy::math::Vec3Swizzles;
@@ -17,12 +19,15 @@ impl Plugin for EffectPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<CurrentEffects>()
             .add_system(end_effects.in_set(OnUpdate(GameState::Playing)))
-            .add_system(move_bird.before(PhysicsSystems::CalculateVelocities));
+            .add_system(end_all_effects.in_schedule(OnEnter(GameState::Restart)))
+            .add_system(end_all_effects.in_schedule(OnEnter(GameState::Menu)))
+            .add_system(move_bird.before(PhysicsSystems::CalculateVelocities))
+            .add_system(retune_controls.run_if(resource_changed::<Tuning>()));
     }
 }
 
 #[derive(Resource, Default)]
-struct CurrentEffects(HashMap<Effect, f32>);
+pub struct CurrentEffects(pub(crate) HashMap<Effect, f32>);
 
 #[derive(Clone, Hash, PartialEq, Eq, Debug)]
 pub enum Effect {
@@ -46,13 +51,14 @@ impl Distribution<Effect> for Standard {
 }
 
 impl Effect {
-    fn duration(&self) -> f32 {
+    fn duration(&self, tuning: &Tuning) -> f32 {
+        let durations = &tuning.effect_durations;
         match self {
-            Effect::Faster => 5.,
-            Effect::JumpPower => 5.,
-            Effect::Shrink => 10.,
-            Effect::Grow => 10.,
-            Effect::Bird => 30.,
+            Effect::Faster => durations.faster,
+            Effect::JumpPower => durations.jump_power,
+            Effect::Shrink => durations.shrink,
+            Effect::Grow => durations.grow,
+            Effect::Bird => durations.bird,
         }
     }
 }
@@ -65,14 +71,19 @@ impl Command for StartEffect {
         let current_effects = world.get_resource_mut::<CurrentEffects>().unwrap();
         if !current_effects.0.contains_key(&self.0) {
             info!("Starting effect {:?}", self.0);
+            world
+                .resource_mut::<Events<SoundEvent>>()
+                .send(SoundEvent::centred(Sound::EffectStart));
             match self.0 {
                 Effect::Faster => {
+                    let speed = world.resource::<Tuning>().faster_speed;
                     let mut controls = world.get_resource_mut::<PlayerControls>().unwrap();
-                    controls.speed = 350.;
+                    controls.speed = speed;
                 }
                 Effect::JumpPower => {
+                    let jump_power = world.resource::<Tuning>().boosted_jump_power;
                     let mut controls = world.get_resource_mut::<PlayerControls>().unwrap();
-                    controls.jump_power = 1400.;
+                    controls.jump_power = jump_power;
                 }
                 Effect::Shrink => {
                     let mut query =
@@ -99,8 +110,8 @@ impl Command for StartEffect {
             }
         }
         let time = world.get_resource::<Time>().unwrap().elapsed_seconds();
+        let duration = self.0.duration(world.resource::<Tuning>());
         let mut current_events = world.get_resource_mut::<CurrentEffects>().unwrap();
-        let duration = self.0.duration();
         current_events.0.insert(self.0, time + duration);
     }
 }
@@ -112,18 +123,23 @@ impl Command for EndEffect {
         info!("Ending effect {:?}", self.0);
         match self.0 {
             Effect::Faster => {
+                let speed = world.resource::<Tuning>().speed;
                 let mut controls = world.get_resource_mut::<PlayerControls>().unwrap();
-                controls.speed = PlayerControls::default().speed;
+                controls.speed = speed;
             }
             Effect::JumpPower => {
+                let jump_power = world.resource::<Tuning>().jump_power;
                 let mut controls = world.get_resource_mut::<PlayerControls>().unwrap();
-                controls.jump_power = PlayerControls::default().jump_power;
+                controls.jump_power = jump_power;
             }
             Effect::Bird => {}
             Effect::Shrink | Effect::Grow => {
                 let mut query =
                     world.query_filtered::<(&mut Transform, &mut Collider), With<Player>>();
-                let (mut transform, mut collider) = query.single_mut(world);
+                // the player might already be gone when leaving to the menu
+                let Ok((mut transform, mut collider)) = query.get_single_mut(world) else {
+                    return;
+                };
                 transform.scale = Vec3::splat(2.);
                 if self.0 == Effect::Shrink {
                     transform.translation.y += 6.5;
@@ -138,10 +154,12 @@ fn end_effects(
     mut commands: Commands,
     mut current_effects: ResMut<CurrentEffects>,
     time: Res<Time>,
+    mut sounds: EventWriter<SoundEvent>,
 ) {
     current_effects.0.retain(|effect, end| {
         if *end < time.elapsed_seconds() {
             commands.add(EndEffect(effect.clone()));
+            sounds.send(SoundEvent::centred(Sound::EffectEnd));
 
             false
         } else {
@@ -150,6 +168,31 @@ fn end_effects(
     });
 }
 
+/// Keep the controls in line with changed tuning, including running effects
+fn retune_controls(
+    tuning: Res<Tuning>,
+    current_effects: Res<CurrentEffects>,
+    mut controls: ResMut<PlayerControls>,
+) {
+    controls.speed = if current_effects.0.contains_key(&Effect::Faster) {
+        tuning.faster_speed
+    } else {
+        tuning.speed
+    };
+    controls.jump_power = if current_effects.0.contains_key(&Effect::JumpPower) {
+        tuning.boosted_jump_power
+    } else {
+        tuning.jump_power
+    };
+}
+
+/// Effects must not carry over into the next run
+fn end_all_effects(mut commands: Commands, mut current_effects: ResMut<CurrentEffects>) {
+    for (effect, _) in current_effects.0.drain() {
+        commands.add(EndEffect(effect));
+    }
+}
+
 #[derive(Component)]
 pub struct Bird;
 
@@ -160,19 +203,21 @@ struct PlayerQuery {
 }
 
 pub const BIRD_Z: f32 = 11.;
+/// Birds fly in from this far behind the player
+const BIRD_SPAWN_DISTANCE: f32 = 400.;
 
 fn spawn_bird(world: &mut World) {
     let bird_texture = world.get_resource::<TextureAssets>().unwrap().bird.clone();
     let mut query = world.query_filtered::<&Transform, With<Player>>();
     let player_query = query.single(world);
+    let position = Vec2::new(
+        player_query.translation.x - BIRD_SPAWN_DISTANCE,
+        LEVEL_HEIGHT,
+    );
     world
         .spawn(SpriteBundle {
             texture: bird_texture,
-            transform: Transform::from_translation(Vec3::new(
-                player_query.translation.x - WIDTH / 2.,
-                HEIGHT,
-                BIRD_Z,
-            )),
+            transform: Transform::from_translation(position.extend(BIRD_Z)),
             ..default()
         })
         .insert(Collider {
@@ -182,24 +227,28 @@ fn spawn_bird(world: &mut World) {
         .insert(Level)
         .insert(Bird)
         .insert(Move);
+    world
+        .resource_mut::<Events<SoundEvent>>()
+        .send(SoundEvent::at(Sound::BirdSwoop, position));
 }
 
 fn move_bird(
     mut bird: Query<(Entity, &mut Velocity, &Transform), With<Bird>>,
     target: Query<&Transform, (With<Player>, Without<Bird>)>,
     current_effects: ResMut<CurrentEffects>,
+    tuning: Res<Tuning>,
     mut commands: Commands,
 ) {
     for (bird, mut velocity, bird_transform) in bird.iter_mut() {
-        if bird_transform.translation.y > HEIGHT * 1.2 {
+        if bird_transform.translation.y > LEVEL_HEIGHT * 1.2 {
             commands.entity(bird).despawn();
             continue;
         }
         if !current_effects.0.contains_key(&Effect::Bird) {
-            velocity.0 = Vec2::splat(1.) * 200.;
+            velocity.0 = Vec2::splat(1.) * tuning.bird_speed;
         } else {
             let diff = target.single().translation.xy() - bird_transform.translation.xy();
-            velocity.0 = diff.normalize() * 200.;
+            velocity.0 = diff.normalize() * tuning.bird_speed;
         }
     }
 }
@@ -1,14 +1,17 @@
+use crate::audio::{Sound, SoundEvent};
 use crate::effects::StartEffect;
-use crate::loading::{AudioAssets, TextureAssets};
+use crate::loading::TextureAssets;
 use crate::map::{Collider, Level, TILE_SIZE};
 use crate::physics::PhysicsSystems;
 use crate::player::{Hunger, Player};
+use crate::seed::EffectRng;
+use crate::tuning::Tuning;
 use crate::ui::Score;
 use crate::GameState;
 use bevy::math::Vec3Swizzles;
 use bevy::prelude::*;
-use bevy_kira_audio::{Audio, AudioControl};
-use rand::prelude::*;
+use rand::rngs::StdRng;
+use rand::Rng;
 
 pub const FOOD_SIZE: f32 = 16.;
 pub const FOOD_Z: f32 = 9.;
@@ -17,90 +20,125 @@ pub struct FoodPlugin;
 
 impl Plugin for FoodPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            (eat, collect)
-                .after(PhysicsSystems::Move)
-                .in_set(OnUpdate(GameState::Playing)),
-        );
+        app.add_event::<FoodEaten>()
+            .add_event::<TruffleCollected>()
+            .add_systems(
+                (eat, collect)
+                    .after(PhysicsSystems::Move)
+                    .in_set(OnUpdate(GameState::Playing)),
+            );
     }
 }
 
+/// Sent whenever the player eats a piece of food
+pub struct FoodEaten {
+    pub position: Vec2,
+    /// Hunger restored by the food
+    pub value: f32,
+}
+
+/// Sent whenever the player collects a truffle
+pub struct TruffleCollected {
+    pub position: Vec2,
+    /// Hunger restored by the truffle
+    pub value: f32,
+}
+
+#[allow(clippy::too_many_arguments)]
 fn eat(
     mut commands: Commands,
     player: Query<(&Transform, &Collider), With<Player>>,
-    food: Query<(Entity, &Transform, &Collider, &Food), Without<Player>>,
+    food: Query<(Entity, &Transform, &Collider), (With<Food>, Without<Player>)>,
     mut hunger: ResMut<Hunger>,
-    audio_assets: Res<AudioAssets>,
-    audio: Res<Audio>,
+    tuning: Res<Tuning>,
+    mut effect_rng: ResMut<EffectRng>,
+    mut food_eaten: EventWriter<FoodEaten>,
+    mut sounds: EventWriter<SoundEvent>,
 ) {
     let (player_transform, player_collider) = player.single();
     let player_rect =
         Rect::from_center_size(player_transform.translation.xy(), player_collider.size);
-    for (food, food_transform, food_collider, food_value) in &food {
+    for (food, food_transform, food_collider) in &food {
         let food_rect = Rect::from_center_size(food_transform.translation.xy(), food_collider.size);
         if !food_rect.intersect(player_rect).is_empty() {
-            hunger.0 += food_value.value;
-            audio.play(audio_assets.eating.clone()).with_volume(0.05);
-            commands.add(StartEffect(random()));
-            hunger.0 = hunger.0.clamp(0., 100.);
+            hunger.0 += tuning.food_value;
+            food_eaten.send(FoodEaten {
+                position: food_transform.translation.xy(),
+                value: tuning.food_value,
+            });
+            sounds.send(SoundEvent::at(Sound::Eat, food_transform.translation.xy()));
+            commands.add(StartEffect(effect_rng.0.gen()));
+            hunger.0 = hunger.0.clamp(0., tuning.max_hunger);
             commands.entity(food).despawn();
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn collect(
     mut commands: Commands,
     player: Query<(&Transform, &Collider), With<Player>>,
-    food: Query<(Entity, &Transform, &Collider, &Truffle), Without<Player>>,
+    food: Query<(Entity, &Transform, &Collider), (With<Truffle>, Without<Player>)>,
     mut hunger: ResMut<Hunger>,
+    tuning: Res<Tuning>,
     mut score: ResMut<Score>,
+    mut truffle_collected: EventWriter<TruffleCollected>,
+    mut sounds: EventWriter<SoundEvent>,
 ) {
     let (player_transform, player_collider) = player.single();
     let player_rect =
         Rect::from_center_size(player_transform.translation.xy(), player_collider.size);
-    for (truffle, food_transform, food_collider, truffle_value) in &food {
+    for (truffle, food_transform, food_collider) in &food {
         let food_rect = Rect::from_center_size(food_transform.translation.xy(), food_collider.size);
         if !food_rect.intersect(player_rect).is_empty() {
             score.0 += 1.;
-            hunger.0 += truffle_value.value;
-            hunger.0 = hunger.0.clamp(0., 100.);
+            hunger.0 += tuning.truffle_value;
+            hunger.0 = hunger.0.clamp(0., tuning.max_hunger);
+            truffle_collected.send(TruffleCollected {
+                position: food_transform.translation.xy(),
+                value: tuning.truffle_value,
+            });
+            sounds.send(SoundEvent::at(
+                Sound::Truffle,
+                food_transform.translation.xy(),
+            ));
             commands.entity(truffle).despawn();
         }
     }
 }
 
 #[derive(Component)]
-pub struct Food {
-    value: f32,
-}
+pub struct Food;
 
 #[derive(Component)]
-pub struct Truffle {
-    value: f32,
-}
+pub struct Truffle;
 
 pub fn spawn_random_food(
     textures: &TextureAssets,
     commands: &mut Commands,
     tile: Vec2,
-    random: &mut ThreadRng,
+    random: &mut StdRng,
 ) {
     let food_index = random.gen_range(0..textures.food.len());
     let food_texture = textures.food.get(food_index).unwrap().clone();
+    spawn_food(
+        commands,
+        food_texture,
+        Vec2::new(tile.x, tile.y + TILE_SIZE / 2. + FOOD_SIZE / 2.),
+    );
+}
+
+pub fn spawn_food(commands: &mut Commands, texture: Handle<Image>, position: Vec2) {
     commands
         .spawn(SpriteBundle {
-            transform: Transform::from_translation(Vec3::new(
-                tile.x,
-                tile.y + TILE_SIZE / 2. + FOOD_SIZE / 2.,
-                FOOD_Z,
-            )),
-            texture: food_texture,
+            transform: Transform::from_translation(position.extend(FOOD_Z)),
+            texture,
             ..default()
         })
         .insert(Collider {
             size: Vec2::splat(16.),
         })
-        .insert(Food { value: 3. })
+        .insert(Food)
         .insert(Level);
 }
 
@@ -118,6 +156,6 @@ pub fn spawn_truffle(textures: &TextureAssets, commands: &mut Commands, tile: Ve
         .insert(Collider {
             size: Vec2::splat(16.),
         })
-        .insert(Truffle { value: 5. })
+        .insert(Truffle)
         .insert(Level);
 }
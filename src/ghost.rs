@@ -0,0 +1,343 @@
+use crate::loading::{FontAssets, TextureAssets};
+use crate::map::TILE_SIZE;
+use crate::physics::PhysicsSystems;
+use crate::player::{Player, PLAYER_Z};
+use crate::replay::Replay;
+use crate::reset::run_finished;
+use crate::seed::{reseed, SeedOverride};
+use crate::settings::config_path;
+use crate::ui::Score;
+use crate::GameState;
+use bevy::prelude::*;
+use std::fs;
+use std::io;
+
+/// Seconds between two recorded ghost frames, positions in between are interpolated
+const GHOST_SAMPLE_INTERVAL: f32 = 0.05;
+const GHOST_ALPHA: f32 = 0.4;
+const GHOST_MAGIC: &[u8; 4] = b"TRGH";
+const GHOST_VERSION: u8 = 1;
+/// Time, position and scale as floats, then a byte of flags and the sprite index
+const FRAME_LEN: usize = 18;
+
+pub struct GhostPlugin;
+
+/// This plugin saves the trajectory of the best run per seed and lets a ghost pig run it again
+/// Only fixed seeds from `--seed` or the daily challenge get a ghost, random ones never repeat
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GhostRecorder>()
+            .init_resource::<BestGhost>()
+            .add_system(
+                start_ghost
+                    .after(reseed)
+                    .in_schedule(OnEnter(GameState::Prepare)),
+            )
+            .add_system(
+                start_ghost
+                    .after(reseed)
+                    .in_schedule(OnExit(GameState::Restart)),
+            )
+            .add_systems(
+                (record_ghost, move_ghost, update_ghost_text)
+                    .chain()
+                    .after(PhysicsSystems::Move)
+                    .in_set(OnUpdate(GameState::Playing)),
+            )
+            .add_system(
+                save_ghost
+                    .run_if(run_finished)
+                    .in_schedule(OnEnter(GameState::Restart)),
+            )
+            .add_system(despawn_ghost.in_schedule(OnEnter(GameState::Restart)))
+            .add_system(despawn_ghost.in_schedule(OnEnter(GameState::Menu)));
+    }
+}
+
+#[derive(Clone, Copy)]
+struct GhostFrame {
+    time: f32,
+    position: Vec2,
+    scale: f32,
+    flip_x: bool,
+    index: usize,
+}
+
+#[derive(Default)]
+struct GhostRun {
+    score: f32,
+    distance: f32,
+    frames: Vec<GhostFrame>,
+}
+
+impl GhostRun {
+    fn path(seed: u64) -> String {
+        format!("ghosts/{seed}.ghost")
+    }
+
+    /// The saved best run on this seed, `None` if there is none or it cannot be read
+    fn load(seed: u64) -> Option<Self> {
+        let path = config_path(&GhostRun::path(seed))?;
+        let bytes = fs::read(&path).ok()?;
+        GhostRun::from_bytes(&bytes)
+            .map_err(|error| warn!("Failed to read ghost {path:?}: {error}"))
+            .ok()
+    }
+
+    fn save(&self, seed: u64) {
+        let Some(path) = config_path(&GhostRun::path(seed)) else {
+            return;
+        };
+        if let Err(error) = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, self.to_bytes()))
+        {
+            warn!("Failed to save ghost to {path:?}: {error}");
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(GHOST_MAGIC.len() + 9 + self.frames.len() * FRAME_LEN);
+        bytes.extend_from_slice(GHOST_MAGIC);
+        bytes.push(GHOST_VERSION);
+        bytes.extend_from_slice(&self.score.to_le_bytes());
+        bytes.extend_from_slice(&self.distance.to_le_bytes());
+        for frame in &self.frames {
+            for value in [frame.time, frame.position.x, frame.position.y, frame.scale] {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+            bytes.push(u8::from(frame.flip_x));
+            bytes.push(frame.index as u8);
+        }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message);
+        let Some(rest) = bytes.strip_prefix(GHOST_MAGIC.as_slice()) else {
+            return Err(invalid("not a ghost file"));
+        };
+        let [version, rest @ ..] = rest else {
+            return Err(invalid("missing ghost version"));
+        };
+        if *version != GHOST_VERSION {
+            return Err(invalid("unsupported ghost version"));
+        }
+        if rest.len() < 8 || (rest.len() - 8) % FRAME_LEN != 0 {
+            return Err(invalid("truncated ghost"));
+        }
+        let float = |bytes: &[u8]| f32::from_le_bytes(bytes.try_into().unwrap());
+        let (header, frames) = rest.split_at(8);
+        Ok(GhostRun {
+            score: float(&header[0..4]),
+            distance: float(&header[4..8]),
+            frames: frames
+                .chunks_exact(FRAME_LEN)
+                .map(|frame| GhostFrame {
+                    time: float(&frame[0..4]),
+                    position: Vec2::new(float(&frame[4..8]), float(&frame[8..12])),
+                    scale: float(&frame[12..16]),
+                    flip_x: frame[16] != 0,
+                    index: frame[17] as usize,
+                })
+                .collect(),
+        })
+    }
+
+    fn beats(&self, other: &GhostRun) -> bool {
+        (self.score, self.distance) > (other.score, other.distance)
+    }
+
+    /// The interpolated frame at the given run time, `None` once the run is over
+    fn frame_at(&self, time: f32) -> Option<GhostFrame> {
+        let next = self.frames.partition_point(|frame| frame.time <= time);
+        let after = *self.frames.get(next)?;
+        let Some(before) = next.checked_sub(1).map(|index| self.frames[index]) else {
+            return Some(after);
+        };
+        let progress = (time - before.time) / (after.time - before.time);
+        Some(GhostFrame {
+            time,
+            position: before.position.lerp(after.position, progress),
+            ..before
+        })
+    }
+}
+
+/// Trajectory of the current run
+#[derive(Resource, Default)]
+struct GhostRecorder {
+    /// `None` while runs use random seeds, nothing is recorded then
+    seed: Option<u64>,
+    elapsed: f32,
+    run: GhostRun,
+}
+
+/// The best run on the current seed
+#[derive(Resource, Default)]
+struct BestGhost(Option<GhostRun>);
+
+#[derive(Component)]
+struct Ghost;
+
+#[derive(Component)]
+struct GhostText;
+
+fn start_ghost(
+    mut commands: Commands,
+    seed_override: Res<SeedOverride>,
+    mut recorder: ResMut<GhostRecorder>,
+    mut best: ResMut<BestGhost>,
+    textures: Res<TextureAssets>,
+    font_assets: Res<FontAssets>,
+) {
+    *recorder = GhostRecorder {
+        seed: seed_override.0,
+        ..default()
+    };
+    best.0 = recorder.seed.and_then(GhostRun::load);
+    if best.0.is_none() {
+        return;
+    }
+    commands
+        .spawn(SpriteSheetBundle {
+            texture_atlas: textures.pig.clone(),
+            sprite: TextureAtlasSprite {
+                color: Color::rgba(1., 1., 1., GHOST_ALPHA),
+                ..TextureAtlasSprite::new(0)
+            },
+            // stay hidden until the first frame is placed
+            visibility: Visibility::Hidden,
+            ..default()
+        })
+        .insert(Ghost);
+    commands
+        .spawn(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: font_assets.fira_sans.clone(),
+                    font_size: 30.0,
+                    color: Color::rgb_u8(34, 32, 52),
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.),
+                    top: Val::Px(70.),
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(GhostText);
+}
+
+fn record_ghost(
+    time: Res<Time>,
+    mut recorder: ResMut<GhostRecorder>,
+    player: Query<(&Transform, &TextureAtlasSprite), With<Player>>,
+) {
+    let Ok((transform, sprite)) = player.get_single() else {
+        return;
+    };
+    if recorder.seed.is_none() {
+        return;
+    }
+    recorder.elapsed += time.delta_seconds();
+    let elapsed = recorder.elapsed;
+    let run = &mut recorder.run;
+    run.distance = run.distance.max(transform.translation.x);
+    if run
+        .frames
+        .last()
+        .map_or(false, |frame| elapsed - frame.time < GHOST_SAMPLE_INTERVAL)
+    {
+        return;
+    }
+    run.frames.push(GhostFrame {
+        time: elapsed,
+        position: transform.translation.truncate(),
+        scale: transform.scale.x,
+        flip_x: sprite.flip_x,
+        index: sprite.index,
+    });
+}
+
+fn move_ghost(
+    recorder: Res<GhostRecorder>,
+    best: Res<BestGhost>,
+    mut ghost: Query<(&mut Transform, &mut TextureAtlasSprite, &mut Visibility), With<Ghost>>,
+) {
+    let (Some(best), Ok((mut transform, mut sprite, mut visibility))) =
+        (&best.0, ghost.get_single_mut())
+    else {
+        return;
+    };
+    let Some(frame) = best.frame_at(recorder.elapsed) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Inherited;
+    transform.translation = frame.position.extend(PLAYER_Z - 0.5);
+    transform.scale = Vec3::splat(frame.scale);
+    sprite.flip_x = frame.flip_x;
+    sprite.index = frame.index;
+}
+
+fn update_ghost_text(
+    recorder: Res<GhostRecorder>,
+    best: Res<BestGhost>,
+    player: Query<&Transform, With<Player>>,
+    mut text: Query<&mut Text, With<GhostText>>,
+) {
+    let (Some(best), Ok(player), Ok(mut text)) =
+        (&best.0, player.get_single(), text.get_single_mut())
+    else {
+        return;
+    };
+    // the best run ended here, so everything after counts against its furthest point
+    let ghost_x = best
+        .frame_at(recorder.elapsed)
+        .map_or(best.distance, |frame| frame.position.x);
+    let lead = (player.translation.x - ghost_x) / TILE_SIZE;
+    text.sections[0].value = if lead >= 0. {
+        format!("Ahead {lead:.0}m")
+    } else {
+        format!("Behind {:.0}m", -lead)
+    };
+}
+
+fn save_ghost(
+    mut recorder: ResMut<GhostRecorder>,
+    mut best: ResMut<BestGhost>,
+    score: Res<Score>,
+    replay: Option<Res<Replay>>,
+) {
+    let Some(seed) = recorder.seed else {
+        return;
+    };
+    if replay.is_some() || recorder.run.frames.is_empty() {
+        return;
+    }
+    recorder.run.score = score.0;
+    if best
+        .0
+        .as_ref()
+        .map_or(false, |best| !recorder.run.beats(best))
+    {
+        return;
+    }
+    info!("New best run on seed {seed}");
+    let run = std::mem::take(&mut recorder.run);
+    run.save(seed);
+    best.0 = Some(run);
+}
+
+fn despawn_ghost(mut commands: Commands, ghost: Query<Entity, Or<(With<Ghost>, With<GhostText>)>>) {
+    for entity in &ghost {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,85 +1,139 @@
 #![allow(clippy::type_complexity)]
 
 mod actions;
+mod animation;
 mod audio;
+mod bot;
 mod camera;
+mod controls;
+mod daily;
+#[cfg(feature = "debug-tools")]
+mod debug;
+mod distance;
 mod effects;
 mod food;
+mod ghost;
 mod loading;
 mod map;
 mod menu;
+mod minimap;
+mod music;
+mod navigation;
+mod pause;
 mod physics;
 mod player;
+mod replay;
 mod reset;
+mod seed;
+mod settings;
+mod shake;
+pub mod simulation;
+mod tuning;
 mod ui;
 
 use crate::actions::ActionsPlugin;
-use crate::audio::InternalAudioPlugin;
+use crate::animation::PlayerAnimationPlugin;
+use crate::audio::{InternalAudioPlugin, SoundEvent};
 use crate::loading::LoadingPlugin;
 use crate::menu::MenuPlugin;
+use crate::minimap::MinimapPlugin;
+use crate::navigation::NavigationPlugin;
+use crate::pause::PausePlugin;
 use crate::player::PlayerPlugin;
 
 use crate::camera::CameraPlugin;
+use crate::controls::ControlsPlugin;
+use crate::daily::DailyPlugin;
+use crate::distance::DistancePlugin;
 use crate::effects::EffectPlugin;
 use crate::food::FoodPlugin;
+use crate::ghost::GhostPlugin;
 use crate::map::MapPlugin;
 use crate::physics::PhysicsPlugin;
-use crate::reset::ResetPlugin;
+use crate::replay::ReplayPlugin;
+use crate::reset::{ResetPlugin, RestartMenuPlugin};
+use crate::seed::SeedPlugin;
+use crate::settings::SettingsPlugin;
+use crate::shake::ShakePlugin;
+use crate::tuning::TuningPlugin;
 use crate::ui::UiPlugin;
 use bevy::app::App;
-#[cfg(debug_assertions)]
-use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
 use bevy::prelude::*;
-use bevy_editor_pls::prelude::*;
 
+pub use crate::map::StartChunk;
+pub use crate::player::GodMode;
+pub use crate::replay::{PendingReplay, Recording};
+pub use crate::seed::SeedOverride;
+pub use crate::settings::Settings;
+pub use crate::tuning::Tuning;
+
+/// Window size when the game starts, the window can be resized freely
 pub const WIDTH: f32 = 800.;
 pub const HEIGHT: f32 = 600.;
 
 #[derive(States, Default, Clone, Eq, PartialEq, Debug, Hash)]
-enum GameState {
+pub enum GameState {
     #[default]
     Loading,
     Menu,
     Prepare,
     Playing,
+    Paused,
     Restart,
+    // an asset failed to load, there is no way to continue
+    LoadingFailed,
 }
 
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_state::<GameState>()
-            .add_system(start_level.in_set(OnUpdate(GameState::Prepare)))
+        app.add_plugin(GameplayPlugin)
             .add_plugin(LoadingPlugin)
             .add_plugin(MenuPlugin)
+            .add_plugin(NavigationPlugin)
+            .add_plugin(PausePlugin)
+            .add_plugin(RestartMenuPlugin)
+            .add_plugin(SettingsPlugin)
+            .add_plugin(ControlsPlugin)
             .add_plugin(ActionsPlugin)
             .add_plugin(InternalAudioPlugin)
+            .add_plugin(PlayerAnimationPlugin)
+            .add_plugin(CameraPlugin)
+            .add_plugin(ShakePlugin)
+            .add_plugin(UiPlugin)
+            .add_plugin(MinimapPlugin)
+            .add_plugin(ReplayPlugin)
+            .add_plugin(GhostPlugin)
+            .add_plugin(DailyPlugin)
+            .add_plugin(DistancePlugin)
+            .add_plugin(TuningPlugin);
+
+        #[cfg(feature = "debug-tools")]
+        app.add_plugin(debug::DebugPlugin);
+    }
+}
+
+struct GameplayPlugin;
+
+/// This plugin holds the game rules without anything that needs a window, assets or audio
+/// That way the same rules can run headless, see [`simulation`]
+impl Plugin for GameplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<GameState>()
+            .init_resource::<Tuning>()
+            .add_event::<SoundEvent>()
+            .add_system(start_level.in_set(OnUpdate(GameState::Prepare)))
+            .add_plugin(SeedPlugin)
             .add_plugin(PlayerPlugin)
             .add_plugin(PhysicsPlugin)
             .add_plugin(MapPlugin)
-            .add_plugin(CameraPlugin)
-            .add_plugin(UiPlugin)
             .add_plugin(FoodPlugin)
             .add_plugin(ResetPlugin)
             .add_plugin(EffectPlugin);
-
-        #[cfg(debug_assertions)]
-        {
-            app.add_plugin(FrameTimeDiagnosticsPlugin::default())
-                .add_plugin(LogDiagnosticsPlugin::default())
-                .add_plugin(EditorPlugin::new().in_new_window(Window::default()))
-                .add_system(reset);
-        }
     }
 }
 
 fn start_level(mut state: ResMut<NextState<GameState>>) {
     state.set(GameState::Playing);
 }
-
-fn reset(mut state: ResMut<NextState<GameState>>, input: Res<Input<KeyCode>>) {
-    if input.just_pressed(KeyCode::R) {
-        state.set(GameState::Restart);
-    }
-}
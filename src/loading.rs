@@ -1,22 +1,40 @@
+use crate::animation::PlayerClips;
+use crate::tuning::Tuning;
 use crate::GameState;
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
 use bevy::render::render_resource::{AddressMode, SamplerDescriptor};
 use bevy::render::texture::ImageSampler;
 use bevy_asset_loader::prelude::*;
 use bevy_kira_audio::AudioSource;
+use iyes_progress::{ProgressCounter, ProgressPlugin};
 use rand::rngs::ThreadRng;
-use rand::Rng;
+use rand::{thread_rng, Rng};
+
+const TIPS: [&str; 6] = [
+    "Truffles are worth more than any other food",
+    "Food fills your hunger, but you never know what else it does",
+    "Jump over holes, you will not get out of them again",
+    "Birds only chase you for a while",
+    "Every day brings a new daily run",
+    "Your best run on a seed races you as a ghost",
+];
+const TIP_SECONDS: f32 = 4.;
+const PROGRESS_BAR_WIDTH: f32 = 400.;
 
 pub struct LoadingPlugin;
 
 /// This plugin loads all assets using [`AssetLoader`] from a third party bevy plugin
 /// Alternatively you can write the logic to load assets yourself
 /// If interested, take a look at <https://bevy-cheatbook.github.io/features/assets.html>
+/// Meanwhile a loading screen shows the progress, or an error if any asset fails to load
 impl Plugin for LoadingPlugin {
     fn build(&self, app: &mut App) {
         app.add_loading_state(
-            LoadingState::new(GameState::Loading).continue_to_state(GameState::Menu),
+            LoadingState::new(GameState::Loading).on_failure_continue_to(GameState::LoadingFailed),
         )
+        // counts every loaded asset of the collections and continues once all are done
+        .add_plugin(ProgressPlugin::new(GameState::Loading).continue_to(GameState::Menu))
         .add_dynamic_collection_to_loading_state::<_, StandardDynamicAssetCollection>(
             GameState::Loading,
             "textures/pig.assets.ron",
@@ -24,25 +42,36 @@ impl Plugin for LoadingPlugin {
         .add_collection_to_loading_state::<_, FontAssets>(GameState::Loading)
         .add_collection_to_loading_state::<_, AudioAssets>(GameState::Loading)
         .add_collection_to_loading_state::<_, TextureAssets>(GameState::Loading)
-        .add_system(configure_samplers.in_schedule(OnExit(GameState::Loading)));
+        .add_collection_to_loading_state::<_, TuningAssets>(GameState::Loading)
+        .add_system(
+            configure_samplers
+                .run_if(resource_exists::<TextureAssets>())
+                .in_schedule(OnExit(GameState::Loading)),
+        )
+        .add_system(setup_loading_screen.in_schedule(OnEnter(GameState::Loading)))
+        .add_systems((update_progress, rotate_tips).in_set(OnUpdate(GameState::Loading)))
+        .add_system(cleanup_loading_screen.in_schedule(OnExit(GameState::Loading)))
+        .add_system(show_loading_error.in_schedule(OnEnter(GameState::LoadingFailed)));
     }
 }
 
 // the following asset collections will be loaded during the State `GameState::Loading`
 // when done loading, they will be inserted as resources (see <https://github.com/NiklasEi/bevy_asset_loader>)
 
-#[derive(AssetCollection, Resource)]
+#[derive(AssetCollection, Resource, Default)]
 pub struct FontAssets {
     #[asset(path = "fonts/FiraSans-Bold.ttf")]
     pub fira_sans: Handle<Font>,
 }
 
-#[derive(AssetCollection, Resource)]
+#[derive(AssetCollection, Resource, Default)]
 pub struct AudioAssets {
     #[asset(path = "audio/birds.ogg")]
     pub birds: Handle<AudioSource>,
     #[asset(path = "audio/eating.ogg")]
     pub eating: Handle<AudioSource>,
+    #[asset(path = "audio/flying.ogg")]
+    pub flying: Handle<AudioSource>,
     #[asset(path = "audio/pig1.ogg")]
     pub pig1: Handle<AudioSource>,
     #[asset(path = "audio/pig2.ogg")]
@@ -61,10 +90,12 @@ impl AudioAssets {
     }
 }
 
-#[derive(AssetCollection, Resource)]
+#[derive(AssetCollection, Resource, Default)]
 pub struct TextureAssets {
     #[asset(key = "pig")]
     pub pig: Handle<TextureAtlas>,
+    #[asset(path = "textures/pig.clips.ron")]
+    pub pig_clips: Handle<PlayerClips>,
     #[asset(path = "textures/ground.png")]
     pub ground: Handle<Image>,
     #[asset(path = "textures/bird.png")]
@@ -96,6 +127,13 @@ pub struct TextureAssets {
     pub food: Vec<Handle<Image>>,
 }
 
+/// Keeps the tuning file loaded, so changes to it are picked up
+#[derive(AssetCollection, Resource)]
+pub struct TuningAssets {
+    #[asset(path = "gameplay.tuning.ron")]
+    pub tuning: Handle<Tuning>,
+}
+
 fn configure_samplers(texture_assets: Res<TextureAssets>, mut textures: ResMut<Assets<Image>>) {
     let repeat_descriptor = SamplerDescriptor {
         address_mode_u: AddressMode::Repeat,
@@ -110,3 +148,160 @@ fn configure_samplers(texture_assets: Res<TextureAssets>, mut textures: ResMut<A
     let mut platform = textures.get_mut(&texture_assets.platform).unwrap();
     platform.sampler_descriptor = ImageSampler::Descriptor(repeat_descriptor.clone());
 }
+
+/// Loaded outside of the collections, so the loading screen can show them right away
+#[derive(Resource)]
+struct LoadingScreenAssets {
+    font: Handle<Font>,
+    logo: Handle<Image>,
+}
+
+#[derive(Component)]
+struct LoadingScreen;
+
+#[derive(Component)]
+struct ProgressBar;
+
+#[derive(Component)]
+struct TipText {
+    timer: Timer,
+    index: usize,
+}
+
+fn setup_loading_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let assets = LoadingScreenAssets {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        logo: asset_server.load("textures/logo.png"),
+    };
+    let tip = thread_rng().gen_range(0..TIPS.len());
+    spawn_screen(&mut commands, &assets).with_children(|parent| {
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Px(PROGRESS_BAR_WIDTH), Val::Px(20.)),
+                    margin: UiRect::all(Val::Px(20.)),
+                    ..default()
+                },
+                background_color: Color::rgb(0.15, 0.15, 0.15).into(),
+                ..default()
+            })
+            .with_children(|parent| {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Percent(0.), Val::Percent(100.)),
+                            ..default()
+                        },
+                        background_color: Color::rgb_u8(238, 166, 173).into(),
+                        ..default()
+                    })
+                    .insert(ProgressBar);
+            });
+        parent
+            .spawn(TextBundle::from_section(
+                TIPS[tip],
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 24.0,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                },
+            ))
+            .insert(TipText {
+                timer: Timer::from_seconds(TIP_SECONDS, TimerMode::Repeating),
+                index: tip,
+            });
+    });
+    commands.insert_resource(assets);
+}
+
+/// A camera and a centered column with the logo, children are added below it
+fn spawn_screen<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    assets: &LoadingScreenAssets,
+) -> EntityCommands<'w, 's, 'a> {
+    commands
+        .spawn(Camera2dBundle::default())
+        .insert(LoadingScreen);
+    let mut root = commands.spawn(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        ..default()
+    });
+    root.insert(LoadingScreen).with_children(|parent| {
+        parent.spawn(ImageBundle {
+            style: Style {
+                size: Size::new(Val::Px(256.), Val::Px(256.)),
+                ..default()
+            },
+            image: assets.logo.clone().into(),
+            ..default()
+        });
+    });
+    root
+}
+
+fn update_progress(
+    time: Res<Time>,
+    progress: Option<Res<ProgressCounter>>,
+    mut progress_bar: Query<&mut Style, With<ProgressBar>>,
+) {
+    let Some(progress) = progress else {
+        return;
+    };
+    let progress = progress.progress();
+    let target = if progress.total == 0 {
+        0.
+    } else {
+        100. * progress.done as f32 / progress.total as f32
+    };
+    for mut style in &mut progress_bar {
+        let Val::Percent(current) = style.size.width else {
+            continue;
+        };
+        // ease towards the target instead of jumping with every asset
+        let step = (target - current) * (1. - (-8. * time.delta_seconds()).exp());
+        style.size.width = Val::Percent(current + step);
+    }
+}
+
+fn rotate_tips(time: Res<Time>, mut tips: Query<(&mut Text, &mut TipText)>) {
+    for (mut text, mut tip) in &mut tips {
+        if tip.timer.tick(time.delta()).just_finished() {
+            tip.index = (tip.index + 1) % TIPS.len();
+            text.sections[0].value = TIPS[tip.index].to_string();
+        }
+    }
+}
+
+fn cleanup_loading_screen(mut commands: Commands, screen: Query<Entity, With<LoadingScreen>>) {
+    for entity in &screen {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn show_loading_error(mut commands: Commands, assets: Res<LoadingScreenAssets>) {
+    error!("Failed to load the game assets, see the errors above for the files involved");
+    spawn_screen(&mut commands, &assets).with_children(|parent| {
+        parent.spawn(
+            TextBundle::from_section(
+                "Some game files could not be loaded.\n\
+                 Make sure the assets folder is complete and restart the game.",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 24.0,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                },
+            )
+            .with_text_alignment(TextAlignment::Center)
+            .with_style(Style {
+                margin: UiRect::all(Val::Px(20.)),
+                ..default()
+            }),
+        );
+    });
+}
@@ -1,29 +1,91 @@
 // disable console on windows for release builds
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use bevy::log::{Level, LogPlugin};
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 use bevy::winit::WinitWindows;
 use bevy::DefaultPlugins;
+use std::env;
 use std::io::Cursor;
-use truffle_run::{GamePlugin, HEIGHT, WIDTH};
+use std::path::PathBuf;
+use std::process;
+use truffle_run::{
+    GamePlugin, GodMode, PendingReplay, Recording, SeedOverride, Settings, StartChunk, HEIGHT,
+    WIDTH,
+};
 use winit::window::Icon;
 
+const USAGE: &str = "Usage: truffle_run [OPTIONS]
+
+Options:
+    --seed <SEED>            Play every run with this seed
+    --window-size <WxH>      Start with this window size, e.g. 1280x720
+    --fullscreen             Start in fullscreen
+    --mute                   Play without any audio
+    --log-level <LEVEL>      One of error, warn, info, debug or trace
+    --replay <FILE>          Watch a recorded run
+    --help                   Print this message
+
+Debug builds only:
+    --start-chunk <CHUNK>    Start runs at this chunk instead of the tutorial
+    --skip-tutorial          Start runs at the first chunk after the tutorial
+    --god-mode               Never die";
+
+/// Flags that only make sense while developing the game
+const DEBUG_FLAGS: [&str; 3] = ["--start-chunk", "--skip-tutorial", "--god-mode"];
+
 fn main() {
-    App::new()
-        .insert_resource(Msaa::Off)
+    let arguments = Arguments::parse(env::args().skip(1)).unwrap_or_else(|error| {
+        eprintln!("{error}\n\n{USAGE}");
+        process::exit(1);
+    });
+
+    let mut settings = Settings::load();
+    settings.muted = arguments.mute;
+    settings.force_fullscreen = arguments.fullscreen;
+    let mut window = Window {
+        title: "Truffle Run".to_string(),
+        resolution: arguments.window_size.unwrap_or((WIDTH, HEIGHT)).into(),
+        canvas: Some("#bevy".to_owned()),
+        // on the web the canvas fills the page and resizes with it
+        fit_canvas_to_parent: true,
+        ..default()
+    };
+    settings.apply_to_window(&mut window);
+
+    let mut app = App::new();
+    if let Some(path) = arguments.replay {
+        match Recording::load(&path) {
+            Ok(recording) => {
+                app.insert_resource(PendingReplay(recording));
+            }
+            Err(error) => {
+                eprintln!("Failed to load replay {path:?}: {error}");
+                process::exit(1);
+            }
+        }
+    }
+    app.insert_resource(Msaa::Off)
+        .insert_resource(settings)
+        .insert_resource(SeedOverride(arguments.seed))
+        .insert_resource(arguments.start_chunk)
+        .insert_resource(GodMode(arguments.god_mode))
         .insert_resource(ClearColor(Color::rgb(0.4, 0.4, 0.4)))
         .add_plugins(
             DefaultPlugins
                 .set(ImagePlugin::default_nearest())
+                .set(AssetPlugin {
+                    // hot reloading of gameplay.tuning.ron
+                    watch_for_changes: !cfg!(target_arch = "wasm32"),
+                    ..default()
+                })
                 .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        title: "Truffle Run".to_string(),
-                        resolution: (WIDTH, HEIGHT).into(),
-                        canvas: Some("#bevy".to_owned()),
-                        resizable: false,
-                        ..default()
-                    }),
+                    primary_window: Some(window),
+                    ..default()
+                })
+                .set(LogPlugin {
+                    level: arguments.log_level,
                     ..default()
                 }),
         )
@@ -32,6 +94,78 @@ fn main() {
         .run();
 }
 
+/// Options given on the command line
+struct Arguments {
+    seed: Option<u64>,
+    start_chunk: StartChunk,
+    window_size: Option<(f32, f32)>,
+    fullscreen: bool,
+    mute: bool,
+    god_mode: bool,
+    log_level: Level,
+    replay: Option<PathBuf>,
+}
+
+impl Arguments {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut arguments = Arguments {
+            seed: None,
+            start_chunk: StartChunk::default(),
+            window_size: None,
+            fullscreen: false,
+            mute: false,
+            god_mode: false,
+            log_level: LogPlugin::default().level,
+            replay: None,
+        };
+        while let Some(flag) = args.next() {
+            if !cfg!(debug_assertions) && DEBUG_FLAGS.contains(&flag.as_str()) {
+                return Err(format!("{flag} is only available in debug builds"));
+            }
+            let mut value = || args.next().ok_or_else(|| format!("{flag} needs a value"));
+            match flag.as_str() {
+                "--seed" => arguments.seed = Some(parse_value(&flag, &value()?)?),
+                "--window-size" => {
+                    let size = value()?;
+                    let (width, height) = size
+                        .split_once('x')
+                        .ok_or_else(|| format!("Invalid value for {flag}: {size}"))?;
+                    arguments.window_size =
+                        Some((parse_value(&flag, width)?, parse_value(&flag, height)?));
+                }
+                "--fullscreen" => arguments.fullscreen = true,
+                "--mute" => arguments.mute = true,
+                "--log-level" => arguments.log_level = parse_value(&flag, &value()?)?,
+                "--replay" => arguments.replay = Some(PathBuf::from(value()?)),
+                "--start-chunk" => {
+                    let chunk = parse_value(&flag, &value()?)?;
+                    if chunk < StartChunk::AFTER_TUTORIAL.0 {
+                        return Err(format!(
+                            "The first chunk after the tutorial is {}",
+                            StartChunk::AFTER_TUTORIAL.0
+                        ));
+                    }
+                    arguments.start_chunk = StartChunk(chunk);
+                }
+                "--skip-tutorial" => arguments.start_chunk = StartChunk::AFTER_TUTORIAL,
+                "--god-mode" => arguments.god_mode = true,
+                "--help" => {
+                    println!("{USAGE}");
+                    process::exit(0);
+                }
+                _ => return Err(format!("Unknown argument {flag}")),
+            }
+        }
+        Ok(arguments)
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value for {flag}: {value}"))
+}
+
 // Sets the icon on windows and X11
 fn set_window_icon(
     windows: NonSend<WinitWindows>,
@@ -1,9 +1,11 @@
 use crate::food::{spawn_random_food, spawn_truffle};
 use crate::loading::TextureAssets;
 use crate::physics::PhysicsSystems;
-use crate::{GameState, HEIGHT, WIDTH};
+use crate::seed::{reseed, MapRng};
+use crate::GameState;
 pub use bevy::prelude::*;
-use rand::{thread_rng, Rng};
+use rand::rngs::StdRng;
+use rand::Rng;
 
 pub const PLATFORM_Z: f32 = 8.;
 pub const PLATFORM_HEIGHT: f32 = TILE_SIZE;
@@ -11,17 +13,41 @@ pub const CHUNK_TILES: usize = 16;
 pub const TILE_SIZE: f32 = 32.;
 pub const CHUNK_WIDTH: f32 = CHUNK_TILES as f32 * TILE_SIZE;
 pub const TUTORIAL_CHUNKS: usize = 5;
+/// Chunks spawned ahead of the one the player is in
+pub const CHUNKS_AHEAD: usize = 2;
+/// Tiles left out of the second tutorial chunk to teach jumping
+const TUTORIAL_HOLE_TILES: [usize; 3] = [10, 11, 12];
+/// The hole in the second tutorial chunk spans these x coordinates
+pub const TUTORIAL_HOLE_START: f32 = CHUNK_WIDTH + TUTORIAL_HOLE_TILES[0] as f32 * TILE_SIZE;
+pub const TUTORIAL_HOLE_END: f32 =
+    CHUNK_WIDTH + (TUTORIAL_HOLE_TILES[TUTORIAL_HOLE_TILES.len() - 1] + 1) as f32 * TILE_SIZE;
+/// Height of the level, the camera always shows all of it
+pub const LEVEL_HEIGHT: f32 = 600.;
+/// Distance of the player's spawn from the left end of the level
+pub const PLAYER_START_X: f32 = 400.;
 pub const MAP_GEN_TRIPPLE_HOLES_FROM_CHUNK: usize = 12;
 pub const MAP_GEN_FOOD_ON_GROUND: f32 = 0.03;
 pub const MAP_GEN_FOOD_ON_PLATFORM: f32 = 0.05;
+/// Metres between two signposts, a tile is one metre
+pub const SIGNPOST_METRES: usize = 50;
 
 pub struct MapPlugin;
 
 impl Plugin for MapPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<CurrentChunk>()
+            .init_resource::<StartChunk>()
             .init_resource::<Holes>()
-            .add_system(setup_map.in_schedule(OnEnter(GameState::Playing)))
+            .add_system(
+                setup_map
+                    .after(reseed)
+                    .in_schedule(OnEnter(GameState::Prepare)),
+            )
+            .add_system(
+                setup_map
+                    .after(reseed)
+                    .in_schedule(OnExit(GameState::Restart)),
+            )
             .add_system(
                 spawn_chunk_system
                     .run_if(in_state(GameState::Playing))
@@ -38,24 +64,48 @@ pub struct Collider {
 #[derive(Component)]
 pub struct Solid;
 
-fn setup_map(mut commands: Commands, textures: Res<TextureAssets>) {
-    commands
-        .spawn(SpriteBundle {
-            transform: Transform::from_translation(Vec3::new(
-                WIDTH / 2.,
-                TILE_SIZE + 60.,
-                PLATFORM_Z,
-            )),
-            texture: textures.control_walk.clone(),
-            ..default()
-        })
-        .insert(Level);
-    let wall = Vec2::new(TILE_SIZE, HEIGHT);
+/// Chunk that runs start in
+///
+/// Runs start with the tutorial unless this is a chunk after it
+#[derive(Resource, Default, Clone, Copy)]
+pub struct StartChunk(pub usize);
+
+impl StartChunk {
+    pub const AFTER_TUTORIAL: StartChunk = StartChunk(TUTORIAL_CHUNKS);
+
+    fn has_tutorial(&self) -> bool {
+        self.0 < TUTORIAL_CHUNKS
+    }
+
+    /// Left end of the level
+    pub fn left_edge(&self) -> f32 {
+        if self.has_tutorial() {
+            0.
+        } else {
+            self.0 as f32 * CHUNK_WIDTH
+        }
+    }
+
+    /// Where the player spawns at the start of a run
+    pub fn player_start(&self) -> Vec2 {
+        Vec2::new(self.left_edge() + PLAYER_START_X, LEVEL_HEIGHT / 2.)
+    }
+}
+
+fn setup_map(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    start_chunk: Res<StartChunk>,
+    mut holes: ResMut<Holes>,
+    mut map_rng: ResMut<MapRng>,
+) {
+    holes.0 = 0;
+    let wall = Vec2::new(TILE_SIZE, LEVEL_HEIGHT);
     commands
         .spawn(SpatialBundle {
             transform: Transform::from_translation(Vec3::new(
-                -TILE_SIZE / 2.,
-                HEIGHT / 2.,
+                start_chunk.left_edge() - TILE_SIZE / 2.,
+                LEVEL_HEIGHT / 2.,
                 PLATFORM_Z,
             )),
             ..default()
@@ -63,7 +113,38 @@ fn setup_map(mut commands: Commands, textures: Res<TextureAssets>) {
         .insert(Collider { size: wall })
         .insert(Solid)
         .insert(Level);
-    spawn_tutorial_chunks(&mut commands, &textures);
+    if start_chunk.has_tutorial() {
+        commands
+            .spawn(SpriteBundle {
+                transform: Transform::from_translation(Vec3::new(
+                    PLAYER_START_X,
+                    TILE_SIZE + 60.,
+                    PLATFORM_Z,
+                )),
+                texture: textures.control_walk.clone(),
+                ..default()
+            })
+            .insert(Level);
+        spawn_tutorial_chunks(&mut commands, &textures, &mut map_rng.0);
+    } else {
+        // solid ground to land on, later chunks are spawned once the player moves
+        for tile in 0..CHUNK_TILES {
+            let center = Vec2::new(
+                start_chunk.left_edge() + TILE_SIZE / 2. + tile as f32 * TILE_SIZE,
+                PLATFORM_HEIGHT / 2.,
+            );
+            let size = Vec2::new(TILE_SIZE, PLATFORM_HEIGHT);
+            spawn_tile(&mut commands, size, center, textures.ground.clone());
+        }
+        spawn_signposts(&mut commands, start_chunk.0);
+        spawn_chunk(
+            &mut commands,
+            &textures,
+            start_chunk.0 + 1,
+            &mut holes,
+            &mut map_rng.0,
+        );
+    }
 }
 
 #[derive(Component)]
@@ -72,10 +153,35 @@ pub struct Level;
 #[derive(Component)]
 pub struct MovingControls;
 
-fn spawn_tutorial_chunks(commands: &mut Commands, textures: &TextureAssets) {
+/// Stands on the ground every [`SIGNPOST_METRES`], showing the distance in metres
+#[derive(Component)]
+pub struct Signpost(pub usize);
+
+fn spawn_signposts(commands: &mut Commands, index: usize) {
+    let first = index * CHUNK_TILES;
+    for metres in first..first + CHUNK_TILES {
+        if metres == 0 || metres % SIGNPOST_METRES != 0 {
+            continue;
+        }
+        commands
+            .spawn(SpatialBundle::from_transform(Transform::from_xyz(
+                metres as f32 * TILE_SIZE,
+                PLATFORM_HEIGHT,
+                PLATFORM_Z - 1.,
+            )))
+            .insert(Signpost(metres))
+            .insert(Level);
+    }
+}
+
+fn spawn_tutorial_chunks(commands: &mut Commands, textures: &TextureAssets, random: &mut StdRng) {
     commands
         .spawn(SpriteBundle {
-            transform: Transform::from_translation(Vec3::new(200., HEIGHT - 150., PLATFORM_Z)),
+            transform: Transform::from_translation(Vec3::new(
+                PLAYER_START_X - 200.,
+                LEVEL_HEIGHT - 150.,
+                PLATFORM_Z,
+            )),
             texture: textures.control_truffles.clone(),
             ..default()
         })
@@ -84,8 +190,8 @@ fn spawn_tutorial_chunks(commands: &mut Commands, textures: &TextureAssets) {
     commands
         .spawn(SpriteBundle {
             transform: Transform::from_translation(Vec3::new(
-                WIDTH - 200.,
-                HEIGHT - 150.,
+                PLAYER_START_X + 200.,
+                LEVEL_HEIGHT - 150.,
                 PLATFORM_Z,
             )),
             texture: textures.control_hunger.clone(),
@@ -113,7 +219,7 @@ fn spawn_tutorial_chunks(commands: &mut Commands, textures: &TextureAssets) {
                     ..default()
                 })
                 .insert(Level);
-            spawn_random_food(&textures, commands, center, &mut thread_rng());
+            spawn_random_food(&textures, commands, center, random);
         }
     }
     for brick in 4..=6 {
@@ -137,13 +243,14 @@ fn spawn_tutorial_chunks(commands: &mut Commands, textures: &TextureAssets) {
         }
     }
     for index in 0..TUTORIAL_CHUNKS {
+        spawn_signposts(commands, index);
         for tile in 0..CHUNK_TILES {
             let center = Vec2::new(
                 index as f32 * CHUNK_WIDTH + TILE_SIZE / 2. + tile as f32 * TILE_SIZE,
                 TILE_SIZE / 2.,
             );
-            if index == 1 && (tile == 10 || tile == 11 || tile == 12) {
-                if tile == 11 {
+            if index == 1 && TUTORIAL_HOLE_TILES.contains(&tile) {
+                if tile == TUTORIAL_HOLE_TILES[1] {
                     commands
                         .spawn(SpriteBundle {
                             transform: Transform::from_translation(Vec3::new(
@@ -187,18 +294,19 @@ fn spawn_chunk(
     textures: &TextureAssets,
     index: usize,
     mut holes: &mut Holes,
+    random: &mut StdRng,
 ) {
     if index < TUTORIAL_CHUNKS {
         return;
     }
     info!("Spawning chunk {index}");
-    let mut random = rand::thread_rng();
+    spawn_signposts(commands, index);
     let hole1 = random.gen_range(0..CHUNK_TILES);
     let hole2 = random.gen_range(0..CHUNK_TILES);
     let platform1 = random.gen_range(2..CHUNK_TILES - 2);
     let platform2 = random.gen_range(2..CHUNK_TILES - 2);
     if hole1 == CHUNK_TILES - 1 || hole2 == CHUNK_TILES - 1 {
-        println!("generating single hole");
+        debug!("generating single hole");
     }
     let mut has_top_platform = false;
     for tile in 0..CHUNK_TILES {
@@ -225,7 +333,7 @@ fn spawn_chunk(
                     textures,
                     commands,
                     center + Vec2::new(0., 4. * TILE_SIZE),
-                    &mut random,
+                    random,
                 );
             }
         }
@@ -262,7 +370,7 @@ fn spawn_chunk(
         holes.0 = 0;
         spawn_tile(commands, size, center, textures.ground.clone());
         if random.gen::<f32>() < MAP_GEN_FOOD_ON_GROUND {
-            spawn_random_food(textures, commands, center, &mut random);
+            spawn_random_food(textures, commands, center, random);
         }
     }
 }
@@ -272,10 +380,17 @@ fn spawn_chunk_system(
     textures: Res<TextureAssets>,
     current_chunk: Res<CurrentChunk>,
     mut holes: ResMut<Holes>,
+    mut map_rng: ResMut<MapRng>,
 ) {
     if !current_chunk.is_changed() {
         return;
     }
 
-    spawn_chunk(&mut commands, &textures, current_chunk.0 + 2, &mut holes);
+    spawn_chunk(
+        &mut commands,
+        &textures,
+        current_chunk.0 + CHUNKS_AHEAD,
+        &mut holes,
+        &mut map_rng.0,
+    );
 }
@@ -1,19 +1,31 @@
-use crate::camera::GameCamera;
+use crate::audio::{Sound, SoundEvent};
+use crate::camera::{game_camera_bundle, GameCamera};
+use crate::daily::DailyMenuState;
 use crate::loading::FontAssets;
-use crate::{GameState, HEIGHT, WIDTH};
+use crate::settings::SettingsMenuState;
+use crate::GameState;
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
 use bevy_parallax::ParallaxCameraComponent;
 
 pub struct MenuPlugin;
 
-/// This plugin is responsible for the game menu (containing only one button...)
+/// This plugin is responsible for the game menu
 /// The menu is only drawn during the State `GameState::Menu` and is removed when that state is exited
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<ButtonColors>()
             .add_system(setup_menu.in_schedule(OnEnter(GameState::Menu)))
-            .add_system(click_play_button.in_set(OnUpdate(GameState::Menu)))
-            .add_system(cleanup_menu.in_schedule(OnExit(GameState::Menu)));
+            .add_system(
+                click_menu_buttons
+                    .in_set(OnUpdate(GameState::Menu))
+                    .run_if(in_state(SettingsMenuState::Closed))
+                    .run_if(in_state(DailyMenuState::Closed)),
+            )
+            .add_system(cleanup_menu.in_schedule(OnExit(GameState::Menu)))
+            .add_system(click_sound)
+            .add_system(button_colors);
     }
 }
 
@@ -32,62 +44,120 @@ impl Default for ButtonColors {
     }
 }
 
+/// The pause menu covers the game, the other overlays are opened on top of it
+pub const PAUSE_OVERLAY_Z: i32 = 5;
+pub const OVERLAY_Z: i32 = 10;
+
+/// Full screen root for a menu shown over the game or another menu
+/// Everything below is darkened and cannot be clicked
+pub fn spawn_overlay<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    z_index: i32,
+) -> EntityCommands<'w, 's, 'a> {
+    commands.spawn(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        background_color: BackgroundColor(Color::rgba(0., 0., 0., 0.8)),
+        focus_policy: FocusPolicy::Block,
+        z_index: ZIndex::Global(z_index),
+        ..Default::default()
+    })
+}
+
 fn setup_menu(
     mut commands: Commands,
     font_assets: Res<FontAssets>,
     button_colors: Res<ButtonColors>,
+    camera: Query<(), With<GameCamera>>,
 ) {
+    if camera.is_empty() {
+        commands
+            .spawn(game_camera_bundle())
+            .insert(GameCamera)
+            .insert(ParallaxCameraComponent);
+    }
     commands
-        .spawn(Camera2dBundle {
-            transform: Transform::from_translation(Vec3::new(WIDTH / 2., HEIGHT / 2., 999.9)),
-            ..default()
-        })
-        .insert(GameCamera)
-        .insert(ParallaxCameraComponent);
-    commands
-        .spawn(ButtonBundle {
+        .spawn(NodeBundle {
             style: Style {
-                size: Size::new(Val::Px(120.0), Val::Px(50.0)),
-                margin: UiRect::all(Val::Auto),
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                flex_direction: FlexDirection::Column,
                 justify_content: JustifyContent::Center,
                 align_items: AlignItems::Center,
                 ..Default::default()
             },
-            background_color: button_colors.normal.into(),
             ..Default::default()
         })
+        .insert(Menu)
         .with_children(|parent| {
-            parent.spawn(TextBundle::from_section(
-                "Play",
-                TextStyle {
-                    font: font_assets.fira_sans.clone(),
-                    font_size: 40.0,
-                    color: Color::rgb(0.9, 0.9, 0.9),
-                },
-            ));
+            for button in [MenuButton::Play, MenuButton::Daily, MenuButton::Settings] {
+                parent
+                    .spawn(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(180.0), Val::Px(50.0)),
+                            margin: UiRect::all(Val::Px(10.)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        background_color: button_colors.normal.into(),
+                        ..Default::default()
+                    })
+                    .insert(button)
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            button.label(),
+                            TextStyle {
+                                font: font_assets.fira_sans.clone(),
+                                font_size: 40.0,
+                                color: Color::rgb(0.9, 0.9, 0.9),
+                            },
+                        ));
+                    });
+            }
         });
 }
 
-fn click_play_button(
-    button_colors: Res<ButtonColors>,
+#[derive(Component)]
+struct Menu;
+
+#[derive(Component, Clone, Copy)]
+enum MenuButton {
+    Play,
+    Daily,
+    Settings,
+}
+
+impl MenuButton {
+    fn label(&self) -> &'static str {
+        match self {
+            MenuButton::Play => "Play",
+            MenuButton::Daily => "Daily",
+            MenuButton::Settings => "Settings",
+        }
+    }
+}
+
+fn click_menu_buttons(
     mut state: ResMut<NextState<GameState>>,
-    mut interaction_query: Query<
-        (&Interaction, &mut BackgroundColor),
-        (Changed<Interaction>, With<Button>),
-    >,
+    mut settings_state: ResMut<NextState<SettingsMenuState>>,
+    mut daily_state: ResMut<NextState<DailyMenuState>>,
+    interaction_query: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
     input: Res<Input<KeyCode>>,
 ) {
-    for (interaction, mut color) in &mut interaction_query {
-        match *interaction {
-            Interaction::Clicked => {
-                state.set(GameState::Prepare);
-            }
-            Interaction::Hovered => {
-                *color = button_colors.hovered.into();
-            }
-            Interaction::None => {
-                *color = button_colors.normal.into();
-            }
+    for (interaction, button) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        match button {
+            MenuButton::Play => state.set(GameState::Prepare),
+            MenuButton::Daily => daily_state.set(DailyMenuState::Open),
+            MenuButton::Settings => settings_state.set(SettingsMenuState::Open),
         }
     }
     if input.just_pressed(KeyCode::Return) {
@@ -95,6 +165,33 @@ fn click_play_button(
     }
 }
 
-fn cleanup_menu(mut commands: Commands, button: Query<Entity, With<Button>>) {
-    commands.entity(button.single()).despawn_recursive();
+fn cleanup_menu(mut commands: Commands, menu: Query<Entity, With<Menu>>) {
+    commands.entity(menu.single()).despawn_recursive();
+}
+
+/// Every button in every menu clicks when pressed
+fn click_sound(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>)>,
+    mut sounds: EventWriter<SoundEvent>,
+) {
+    if buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Clicked)
+    {
+        sounds.send(SoundEvent::centred(Sound::MenuClick));
+    }
+}
+
+/// Every button in every menu is highlighted while hovered or focused
+fn button_colors(
+    button_colors: Res<ButtonColors>,
+    mut buttons: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<Button>)>,
+) {
+    for (interaction, mut color) in &mut buttons {
+        match *interaction {
+            Interaction::Clicked => {}
+            Interaction::Hovered => *color = button_colors.hovered.into(),
+            Interaction::None => *color = button_colors.normal.into(),
+        }
+    }
 }
@@ -0,0 +1,207 @@
+use crate::effects::Bird;
+use crate::food::Truffle;
+use crate::map::{Collider, Solid, CHUNKS_AHEAD, CHUNK_WIDTH, LEVEL_HEIGHT, PLATFORM_HEIGHT};
+use crate::physics::PhysicsSystems;
+use crate::player::Player;
+use crate::settings::Settings;
+use crate::ui::Hud;
+use crate::GameState;
+use bevy::math::Vec3Swizzles;
+use bevy::prelude::*;
+
+const MINIMAP_SIZE: Vec2 = Vec2::new(320., 40.);
+/// Chunks shown on the minimap, starting half a chunk behind the player
+/// The strip never reaches past the last spawned chunk, so its right side is never empty
+const MINIMAP_CHUNKS: f32 = CHUNKS_AHEAD as f32 + 0.5;
+/// Marks are never thinner than this, so single tiles and birds stay visible
+const MIN_MARK_SIZE: f32 = 2.;
+/// Marks are spawned once and reused, anything beyond this is not drawn
+const MAX_MARKS: usize = 160;
+const FRAME_COLOR: Color = Color::rgba(0.7, 0.7, 0.7, 0.7);
+const GROUND_COLOR: Color = Color::rgb(0.4, 0.3, 0.2);
+const PLATFORM_COLOR: Color = Color::rgb(0.55, 0.45, 0.35);
+const TRUFFLE_COLOR: Color = Color::rgb(0.95, 0.8, 0.2);
+const BIRD_COLOR: Color = Color::rgb(0.85, 0.2, 0.2);
+const PLAYER_COLOR: Color = Color::rgb(0.95, 0.5, 0.6);
+
+pub struct MinimapPlugin;
+
+/// This plugin shows a strip at the bottom of the screen with the terrain ahead of the player
+/// It is drawn from the colliders of the spawned chunks and can be turned off in the settings
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_minimap.in_schedule(OnEnter(GameState::Prepare)))
+            .add_system(show_minimap.run_if(resource_changed::<Settings>()))
+            .add_system(
+                draw_minimap
+                    .after(PhysicsSystems::Move)
+                    .in_set(OnUpdate(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct Minimap;
+
+/// One of a fixed set of nodes that are moved and recoloured every frame while playing
+#[derive(Component)]
+struct MinimapMark;
+
+/// Spawned after the other marks, so the player is drawn on top of them
+#[derive(Component)]
+struct MinimapPlayer;
+
+fn spawn_minimap(mut commands: Commands, settings: Res<Settings>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Auto),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                position: UiRect {
+                    bottom: Val::Px(10.),
+                    ..default()
+                },
+                ..default()
+            },
+            visibility: minimap_visibility(&settings),
+            ..default()
+        })
+        .insert(Hud)
+        .insert(Minimap)
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(MINIMAP_SIZE.x), Val::Px(MINIMAP_SIZE.y)),
+                        ..default()
+                    },
+                    background_color: FRAME_COLOR.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for _ in 0..MAX_MARKS {
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    position_type: PositionType::Absolute,
+                                    display: Display::None,
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .insert(MinimapMark);
+                    }
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                display: Display::None,
+                                ..default()
+                            },
+                            background_color: PLAYER_COLOR.into(),
+                            ..default()
+                        })
+                        .insert(MinimapPlayer);
+                });
+        });
+}
+
+fn minimap_visibility(settings: &Settings) -> Visibility {
+    if settings.minimap {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
+
+fn show_minimap(settings: Res<Settings>, mut minimap: Query<&mut Visibility, With<Minimap>>) {
+    for mut visibility in &mut minimap {
+        *visibility = minimap_visibility(&settings);
+    }
+}
+
+fn draw_minimap(
+    settings: Res<Settings>,
+    mut marks: Query<(&mut Style, &mut BackgroundColor), With<MinimapMark>>,
+    mut player_mark: Query<&mut Style, (With<MinimapPlayer>, Without<MinimapMark>)>,
+    player: Query<(&Transform, &Collider), With<Player>>,
+    colliders: Query<
+        (
+            &Transform,
+            &Collider,
+            Option<&Solid>,
+            Option<&Truffle>,
+            Option<&Bird>,
+        ),
+        Without<Player>,
+    >,
+) {
+    let (true, Ok((player, player_collider))) = (settings.minimap, player.get_single()) else {
+        return;
+    };
+    let start = player.translation.x - CHUNK_WIDTH / 2.;
+    let scale = MINIMAP_SIZE / Vec2::new(MINIMAP_CHUNKS * CHUNK_WIDTH, LEVEL_HEIGHT);
+    let to_minimap = |center: Vec2, size: Vec2| {
+        let rect = Rect::from_center_size(
+            (center - Vec2::new(start, 0.)) * scale,
+            (size * scale).max(Vec2::splat(MIN_MARK_SIZE)),
+        );
+        // cut off everything outside of the strip
+        let min = rect.min.max(Vec2::ZERO);
+        let max = rect.max.min(MINIMAP_SIZE);
+        (min.x < max.x && min.y < max.y).then(|| Rect::from_corners(min, max))
+    };
+
+    let mut shown = colliders
+        .iter()
+        .filter_map(|(transform, collider, solid, truffle, bird)| {
+            let color = if bird.is_some() {
+                BIRD_COLOR
+            } else if truffle.is_some() {
+                TRUFFLE_COLOR
+            } else if solid.is_none() {
+                return None;
+            } else if transform.translation.y <= PLATFORM_HEIGHT {
+                GROUND_COLOR
+            } else {
+                PLATFORM_COLOR
+            };
+            Some((
+                to_minimap(transform.translation.xy(), collider.size)?,
+                color,
+            ))
+        });
+    for (mut style, mut color) in &mut marks {
+        let mark = shown.next();
+        place_mark(&mut style, mark.map(|(rect, _)| rect));
+        if let Some((_, mark_color)) = mark {
+            color.0 = mark_color;
+        }
+    }
+    for mut style in &mut player_mark {
+        place_mark(
+            &mut style,
+            to_minimap(player.translation.xy(), player_collider.size),
+        );
+    }
+}
+
+/// Moves a mark over `rect` on the strip, or hides it
+fn place_mark(style: &mut Mut<Style>, rect: Option<Rect>) {
+    let Some(rect) = rect else {
+        // only touch the style if needed, so hidden marks do not cause a layout
+        if style.display != Display::None {
+            style.display = Display::None;
+        }
+        return;
+    };
+    style.display = Display::Flex;
+    style.size = Size::new(Val::Px(rect.width()), Val::Px(rect.height()));
+    // ui goes down from the top, the level goes up from the ground
+    style.position = UiRect {
+        left: Val::Px(rect.min.x),
+        top: Val::Px(MINIMAP_SIZE.y - rect.max.y),
+        ..default()
+    };
+}
@@ -0,0 +1,259 @@
+use crate::effects::Bird;
+use crate::loading::AudioAssets;
+use crate::player::{Hunger, Player};
+use crate::reset::run_finished;
+use crate::settings::Settings;
+use crate::GameState;
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+use bevy_kira_audio::prelude::*;
+use std::time::Duration;
+
+const CROSSFADE: Duration = Duration::from_millis(1500);
+/// Intensity layers take this long to follow the game
+const LAYER_FADE: Duration = Duration::from_millis(800);
+const PAUSE_FADE: Duration = Duration::from_millis(300);
+/// The hungry layer starts fading in below this hunger and is at full volume when starving
+const HUNGRY_BELOW: f32 = 30.;
+/// Birds closer to the player than this bring in the chase layer
+const CHASE_DISTANCE: f32 = 400.;
+
+pub struct MusicPlugin;
+
+/// This plugin plays the music and the ambience, crossfading between the menu, run and game over
+/// While running, extra layers of the track fade in when the pig gets hungry or a bird is close
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_channel::<Music>()
+            .add_audio_channel::<Ambience>()
+            .init_resource::<MusicTracks>()
+            .init_resource::<CurrentMusic>()
+            .add_system(
+                start_ambience
+                    .run_if(resource_exists::<AudioAssets>())
+                    .in_schedule(OnExit(GameState::Loading)),
+            )
+            .add_system(play_track(Track::Menu).in_schedule(OnEnter(GameState::Menu)))
+            .add_system(play_track(Track::Run).in_schedule(OnEnter(GameState::Prepare)))
+            .add_system(
+                play_track(Track::GameOver)
+                    .run_if(run_finished)
+                    .in_schedule(OnEnter(GameState::Restart)),
+            )
+            .add_system(play_track(Track::Run).in_schedule(OnExit(GameState::Restart)))
+            .add_system(pause_music.in_schedule(OnEnter(GameState::Paused)))
+            .add_system(resume_music.in_schedule(OnExit(GameState::Paused)))
+            .add_system(switch_tracks)
+            .add_system(update_layers.in_set(OnUpdate(GameState::Playing)))
+            .add_system(apply_music_volume.after(switch_tracks));
+    }
+}
+
+#[derive(Resource)]
+struct Music;
+
+#[derive(Resource)]
+struct Ambience;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Track {
+    Menu,
+    Run,
+    GameOver,
+}
+
+/// Music is optional, a track that is missing or fails to load is skipped
+#[derive(Resource)]
+struct MusicTracks {
+    menu: Handle<AudioSource>,
+    run: Handle<AudioSource>,
+    /// Plays along with `run` while the pig is hungry
+    hungry: Handle<AudioSource>,
+    /// Plays along with `run` while a bird is chasing
+    chase: Handle<AudioSource>,
+    game_over: Handle<AudioSource>,
+}
+
+impl FromWorld for MusicTracks {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        MusicTracks {
+            menu: asset_server.load("audio/music/menu.wav"),
+            run: asset_server.load("audio/music/run.wav"),
+            hungry: asset_server.load("audio/music/run_hungry.wav"),
+            chase: asset_server.load("audio/music/run_chase.wav"),
+            game_over: asset_server.load("audio/music/game_over.wav"),
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct CurrentMusic {
+    wanted: Option<Track>,
+    playing: Option<Track>,
+    main: Option<Handle<AudioInstance>>,
+    hungry: Option<Handle<AudioInstance>>,
+    chase: Option<Handle<AudioInstance>>,
+    /// Volume of the layers between 0 and 1, before the music volume setting
+    hungry_level: f32,
+    chase_level: f32,
+}
+
+impl CurrentMusic {
+    fn instances(&self) -> impl Iterator<Item = &Handle<AudioInstance>> {
+        [&self.main, &self.hungry, &self.chase]
+            .into_iter()
+            .flatten()
+    }
+}
+
+fn start_ambience(
+    audio: Res<AudioChannel<Ambience>>,
+    audio_assets: Res<AudioAssets>,
+    settings: Res<Settings>,
+) {
+    audio
+        .play(audio_assets.birds.clone())
+        .looped()
+        .with_volume(settings.music_volume());
+}
+
+fn play_track(track: Track) -> impl FnMut(ResMut<CurrentMusic>) {
+    move |mut music| music.wanted = Some(track)
+}
+
+fn switch_tracks(
+    mut music: ResMut<CurrentMusic>,
+    tracks: Res<MusicTracks>,
+    asset_server: Res<AssetServer>,
+    audio: Res<AudioChannel<Music>>,
+    mut instances: ResMut<Assets<AudioInstance>>,
+    settings: Res<Settings>,
+) {
+    let Some(track) = music.wanted else {
+        return;
+    };
+    if music.playing == Some(track) {
+        return;
+    }
+    let source = match track {
+        Track::Menu => &tracks.menu,
+        Track::Run => &tracks.run,
+        Track::GameOver => &tracks.game_over,
+    };
+    let load_state = asset_server.get_load_state(source);
+    if load_state == LoadState::Loading {
+        return;
+    }
+
+    for instance in music.instances() {
+        if let Some(instance) = instances.get_mut(instance) {
+            instance.stop(AudioTween::linear(CROSSFADE));
+        }
+    }
+    music.playing = Some(track);
+    music.main = None;
+    music.hungry = None;
+    music.chase = None;
+    if load_state != LoadState::Loaded {
+        // fade to silence instead of keeping the previous track
+        return;
+    }
+    let volume = settings.music_volume();
+    let start = |source: &Handle<AudioSource>, volume: f64| {
+        let mut command = audio.play(source.clone());
+        command
+            .with_volume(volume)
+            .fade_in(AudioTween::linear(CROSSFADE));
+        if track != Track::GameOver {
+            command.looped();
+        }
+        command.handle()
+    };
+    music.main = Some(start(source, volume));
+    // the layers start silent together with the track, so they stay in sync
+    let layer_loaded =
+        |layer: &Handle<AudioSource>| asset_server.get_load_state(layer) == LoadState::Loaded;
+    let with_layers = track == Track::Run;
+    music.hungry = (with_layers && layer_loaded(&tracks.hungry)).then(|| start(&tracks.hungry, 0.));
+    music.chase = (with_layers && layer_loaded(&tracks.chase)).then(|| start(&tracks.chase, 0.));
+    music.hungry_level = 0.;
+    music.chase_level = 0.;
+}
+
+fn update_layers(
+    mut music: ResMut<CurrentMusic>,
+    mut instances: ResMut<Assets<AudioInstance>>,
+    settings: Res<Settings>,
+    hunger: Res<Hunger>,
+    player: Query<&Transform, With<Player>>,
+    birds: Query<&Transform, With<Bird>>,
+) {
+    let hungry = ((HUNGRY_BELOW - hunger.0) / HUNGRY_BELOW).clamp(0., 1.);
+    let chased = player.get_single().map_or(false, |player| {
+        birds.iter().any(|bird| {
+            bird.translation
+                .truncate()
+                .distance(player.translation.truncate())
+                < CHASE_DISTANCE
+        })
+    });
+    let chase = if chased { 1. } else { 0. };
+
+    let mut fade = |instance: &Option<Handle<AudioInstance>>, level: &mut f32, target: f32| {
+        // only send a new fade once the level changed noticeably
+        if (*level - target).abs() < 0.05 && (target > 0. || *level == 0.) {
+            return;
+        }
+        *level = target;
+        if let Some(instance) = instance
+            .as_ref()
+            .and_then(|handle| instances.get_mut(handle))
+        {
+            instance.set_volume(
+                settings.music_volume() * target as f64,
+                AudioTween::linear(LAYER_FADE),
+            );
+        }
+    };
+    let music = &mut *music;
+    fade(&music.hungry, &mut music.hungry_level, hungry);
+    fade(&music.chase, &mut music.chase_level, chase);
+}
+
+fn pause_music(music: Res<AudioChannel<Music>>, ambience: Res<AudioChannel<Ambience>>) {
+    music.pause().fade_out(AudioTween::linear(PAUSE_FADE));
+    ambience.pause().fade_out(AudioTween::linear(PAUSE_FADE));
+}
+
+fn resume_music(music: Res<AudioChannel<Music>>, ambience: Res<AudioChannel<Ambience>>) {
+    music.resume().fade_in(AudioTween::linear(PAUSE_FADE));
+    ambience.resume().fade_in(AudioTween::linear(PAUSE_FADE));
+}
+
+fn apply_music_volume(
+    music: Res<CurrentMusic>,
+    mut instances: ResMut<Assets<AudioInstance>>,
+    ambience: Res<AudioChannel<Ambience>>,
+    settings: Res<Settings>,
+) {
+    if !settings.is_changed() || settings.is_added() {
+        return;
+    }
+    let volume = settings.music_volume();
+    ambience.set_volume(volume);
+    // the music channel volume would overwrite the layer levels, so set every instance
+    let levels = [
+        (&music.main, 1.),
+        (&music.hungry, music.hungry_level),
+        (&music.chase, music.chase_level),
+    ];
+    for (instance, level) in levels {
+        if let Some(instance) = instance
+            .as_ref()
+            .and_then(|handle| instances.get_mut(handle))
+        {
+            instance.set_volume(volume * level as f64, AudioTween::default());
+        }
+    }
+}
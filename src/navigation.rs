@@ -0,0 +1,158 @@
+use bevy::prelude::*;
+use bevy::ui::{UiStack, UiSystem};
+use bevy::window::CursorMoved;
+
+pub struct NavigationPlugin;
+
+/// This plugin lets a gamepad move between menu buttons with the d-pad and press them with A
+/// The focused button is shown as hovered, so every menu can keep handling `Interaction` only
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FocusedButton>().add_system(
+            navigate_buttons
+                .in_base_set(CoreSet::PreUpdate)
+                .after(UiSystem::Focus),
+        );
+    }
+}
+
+#[derive(Resource, Default)]
+struct FocusedButton {
+    entity: Option<Entity>,
+    /// The focused button was pressed last frame and has to be released again
+    pressed: bool,
+}
+
+type Buttons<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Interaction,
+        &'static GlobalTransform,
+        &'static ComputedVisibility,
+    ),
+    With<Button>,
+>;
+
+fn set_interaction(buttons: &mut Buttons, entity: Option<Entity>, value: Interaction) {
+    let Some(entity) = entity else {
+        return;
+    };
+    if let Ok((mut interaction, ..)) = buttons.get_mut(entity) {
+        interaction.set_if_neq(value);
+    }
+}
+
+fn is_visible(buttons: &Buttons, entity: Entity) -> bool {
+    buttons
+        .get(entity)
+        .map_or(false, |(.., visibility)| visibility.is_visible())
+}
+
+fn navigate_buttons(
+    mut focused: ResMut<FocusedButton>,
+    mut cursor_moved: EventReader<CursorMoved>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    ui_stack: Res<UiStack>,
+    parents: Query<&Parent>,
+    mut buttons: Buttons,
+) {
+    if focused.pressed {
+        focused.pressed = false;
+        set_interaction(&mut buttons, focused.entity, Interaction::None);
+    }
+    if cursor_moved.iter().last().is_some() {
+        focused.entity = None;
+        return;
+    }
+    if let Some(entity) = focused.entity {
+        if !is_visible(&buttons, entity) {
+            focused.entity = None;
+        }
+    }
+
+    let just_pressed = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| gamepad_input.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+    let direction = [
+        (GamepadButtonType::DPadUp, Vec2::NEG_Y),
+        (GamepadButtonType::DPadDown, Vec2::Y),
+        (GamepadButtonType::DPadLeft, Vec2::NEG_X),
+        (GamepadButtonType::DPadRight, Vec2::X),
+    ]
+    .into_iter()
+    .find(|(button_type, _)| just_pressed(*button_type))
+    .map(|(_, direction)| direction);
+    let confirm = just_pressed(GamepadButtonType::South);
+    if direction.is_none() && !confirm {
+        // keep the focus highlighted, `ui_focus_system` resets it when not under the cursor
+        set_interaction(&mut buttons, focused.entity, Interaction::Hovered);
+        return;
+    }
+
+    // Only buttons of the top most menu can be focused
+    let root = |mut entity: Entity| {
+        while let Ok(parent) = parents.get(entity) {
+            entity = parent.get();
+        }
+        entity
+    };
+    let Some(top_root) = ui_stack
+        .uinodes
+        .iter()
+        .rev()
+        .find(|entity| is_visible(&buttons, **entity))
+        .map(|entity| root(*entity))
+    else {
+        return;
+    };
+    let candidates: Vec<(Entity, Vec2)> = ui_stack
+        .uinodes
+        .iter()
+        .filter(|entity| root(**entity) == top_root)
+        .filter(|entity| is_visible(&buttons, **entity))
+        .filter_map(|entity| {
+            let (_, transform, _) = buttons.get(*entity).ok()?;
+            Some((*entity, transform.translation().truncate()))
+        })
+        .collect();
+
+    let current = focused.entity.and_then(|entity| {
+        candidates
+            .iter()
+            .find(|(candidate, _)| *candidate == entity)
+    });
+    let next = match (current, direction) {
+        (None, _) => candidates
+            .iter()
+            .min_by(|(_, a), (_, b)| (a.y, a.x).partial_cmp(&(b.y, b.x)).unwrap())
+            .map(|(entity, _)| *entity),
+        (Some((entity, _)), None) => Some(*entity),
+        (Some((entity, position)), Some(direction)) => candidates
+            .iter()
+            .filter(|(_, candidate)| (*candidate - *position).dot(direction) > 1.)
+            .min_by(|(_, a), (_, b)| {
+                let score = |candidate: Vec2| {
+                    let offset = candidate - *position;
+                    offset.length() + 2. * offset.perp_dot(direction).abs()
+                };
+                score(*a).partial_cmp(&score(*b)).unwrap()
+            })
+            .map(|(next, _)| *next)
+            .or(Some(*entity)),
+    };
+
+    if next != focused.entity {
+        set_interaction(&mut buttons, focused.entity, Interaction::None);
+        focused.entity = next;
+    }
+    if confirm && current.is_some() {
+        set_interaction(&mut buttons, focused.entity, Interaction::Clicked);
+        focused.pressed = true;
+    } else {
+        set_interaction(&mut buttons, focused.entity, Interaction::Hovered);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::actions::{ControlInput, GameControl};
+use crate::loading::FontAssets;
+use crate::menu::{spawn_overlay, ButtonColors, PAUSE_OVERLAY_Z};
+use crate::reset::AbandonedRun;
+use crate::settings::SettingsMenuState;
+use crate::GameState;
+use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent};
+use bevy::prelude::*;
+use bevy::window::WindowFocused;
+use bevy_kira_audio::{Audio, AudioControl};
+
+pub struct PausePlugin;
+
+/// This plugin pauses the game on the pause control, on focus loss or when a gamepad disconnects
+/// While `GameState::Paused` is active, time is stopped and an overlay menu is shown
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (
+                pause_on_input,
+                pause_on_focus_loss,
+                pause_on_gamepad_disconnect,
+            )
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_system(setup_pause_menu.in_schedule(OnEnter(GameState::Paused)))
+        .add_systems(
+            (resume_on_input, click_pause_buttons)
+                .distributive_run_if(in_state(SettingsMenuState::Closed))
+                .in_set(OnUpdate(GameState::Paused)),
+        )
+        .add_system(cleanup_pause_menu.in_schedule(OnExit(GameState::Paused)));
+    }
+}
+
+#[derive(Component)]
+struct PauseMenu;
+
+#[derive(Component, Clone, Copy)]
+enum PauseButton {
+    Resume,
+    Restart,
+    Settings,
+    Quit,
+}
+
+impl PauseButton {
+    fn label(&self) -> &'static str {
+        match self {
+            PauseButton::Resume => "Resume",
+            PauseButton::Restart => "Restart",
+            PauseButton::Settings => "Settings",
+            PauseButton::Quit => "Menu",
+        }
+    }
+}
+
+fn pause_on_input(control_input: ControlInput, mut state: ResMut<NextState<GameState>>) {
+    if control_input.just_pressed(GameControl::Pause) {
+        state.set(GameState::Paused);
+    }
+}
+
+fn pause_on_focus_loss(
+    mut focus_events: EventReader<WindowFocused>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    if focus_events.iter().any(|event| !event.focused) {
+        state.set(GameState::Paused);
+    }
+}
+
+fn pause_on_gamepad_disconnect(
+    mut gamepad_events: EventReader<GamepadConnectionEvent>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    if gamepad_events
+        .iter()
+        .any(|event| event.connection == GamepadConnection::Disconnected)
+    {
+        state.set(GameState::Paused);
+    }
+}
+
+fn resume_on_input(control_input: ControlInput, mut state: ResMut<NextState<GameState>>) {
+    if control_input.just_pressed(GameControl::Pause) {
+        state.set(GameState::Playing);
+    }
+}
+
+fn setup_pause_menu(
+    mut commands: Commands,
+    mut time: ResMut<Time>,
+    audio: Res<Audio>,
+    font_assets: Res<FontAssets>,
+    button_colors: Res<ButtonColors>,
+) {
+    time.pause();
+    audio.pause();
+    spawn_overlay(&mut commands, PAUSE_OVERLAY_Z)
+        .insert(PauseMenu)
+        .with_children(|parent| {
+            for button in [
+                PauseButton::Resume,
+                PauseButton::Restart,
+                PauseButton::Settings,
+                PauseButton::Quit,
+            ] {
+                parent
+                    .spawn(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(180.0), Val::Px(50.0)),
+                            margin: UiRect::all(Val::Px(10.)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        background_color: button_colors.normal.into(),
+                        ..Default::default()
+                    })
+                    .insert(button)
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            button.label(),
+                            TextStyle {
+                                font: font_assets.fira_sans.clone(),
+                                font_size: 40.0,
+                                color: Color::rgb(0.9, 0.9, 0.9),
+                            },
+                        ));
+                    });
+            }
+        });
+}
+
+fn click_pause_buttons(
+    mut state: ResMut<NextState<GameState>>,
+    mut settings_state: ResMut<NextState<SettingsMenuState>>,
+    mut abandoned_run: ResMut<AbandonedRun>,
+    interaction_query: Query<(&Interaction, &PauseButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        match button {
+            PauseButton::Resume => state.set(GameState::Playing),
+            PauseButton::Restart => {
+                abandoned_run.0 = true;
+                state.set(GameState::Restart);
+            }
+            PauseButton::Settings => settings_state.set(SettingsMenuState::Open),
+            PauseButton::Quit => state.set(GameState::Menu),
+        }
+    }
+}
+
+fn cleanup_pause_menu(
+    mut commands: Commands,
+    mut time: ResMut<Time>,
+    audio: Res<Audio>,
+    menu: Query<Entity, With<PauseMenu>>,
+) {
+    time.unpause();
+    audio.resume();
+    for entity in &menu {
+        commands.entity(entity).despawn_recursive();
+    }
+}
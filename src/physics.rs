@@ -1,11 +1,10 @@
 use crate::map::{Collider, CurrentChunk, Solid, CHUNK_WIDTH};
 use crate::player::{Grounded, Player};
+use crate::tuning::Tuning;
 use crate::GameState;
 use bevy::math::Vec3Swizzles;
 use bevy::prelude::*;
 
-const GRAVITY: f32 = 4250.;
-
 pub struct PhysicsPlugin;
 
 impl Plugin for PhysicsPlugin {
@@ -31,9 +30,13 @@ pub enum PhysicsSystems {
 #[derive(Component)]
 pub struct Velocity(pub(crate) Vec2);
 
-fn gravity(time: Res<Time>, mut falling: Query<&mut Velocity, (With<Player>, Without<Grounded>)>) {
+fn gravity(
+    time: Res<Time>,
+    tuning: Res<Tuning>,
+    mut falling: Query<&mut Velocity, (With<Player>, Without<Grounded>)>,
+) {
     for mut velocity in &mut falling {
-        velocity.0.y -= GRAVITY * time.delta_seconds();
+        velocity.0.y -= tuning.gravity * time.delta_seconds();
     }
 }
 
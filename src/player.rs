@@ -1,13 +1,21 @@
 use crate::actions::Actions;
+use crate::audio::{Sound, SoundEvent};
 use crate::effects::Bird;
+use crate::food::{FoodEaten, TruffleCollected};
 use crate::loading::TextureAssets;
-use crate::map::{Collider, TILE_SIZE};
+use crate::map::{Collider, StartChunk, LEVEL_HEIGHT, TILE_SIZE};
 use crate::physics::{Move, PhysicsSystems, Velocity};
-use crate::{GameState, HEIGHT, WIDTH};
+use crate::tuning::Tuning;
+use crate::GameState;
 use bevy::math::Vec3Swizzles;
 use bevy::prelude::*;
+use serde::Deserialize;
 
 pub const PLAYER_Z: f32 = 10.;
+/// How long the pig stays in [`PlayerState::Land`] before running or idling again
+const LAND_SECONDS: f32 = 0.15;
+/// How long the pig stays in [`PlayerState::Eat`]
+const EAT_SECONDS: f32 = 0.4;
 
 pub struct PlayerPlugin;
 
@@ -26,6 +34,11 @@ pub struct Falling(pub(crate) bool);
 #[component(storage = "SparseSet")]
 pub struct Grounded;
 
+/// The player can not die while this is set
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct GodMode(pub bool);
+
 #[derive(Resource, Reflect)]
 #[reflect(Resource)]
 pub struct Hunger(pub(crate) f32);
@@ -36,16 +49,6 @@ impl Default for Hunger {
     }
 }
 
-#[derive(Resource, Reflect)]
-#[reflect(Resource)]
-pub struct HungerPerSecond(f32);
-
-impl Default for HungerPerSecond {
-    fn default() -> Self {
-        HungerPerSecond(1.5)
-    }
-}
-
 #[derive(Resource, Reflect)]
 #[reflect(Resource)]
 pub struct PlayerControls {
@@ -55,23 +58,66 @@ pub struct PlayerControls {
 
 impl Default for PlayerControls {
     fn default() -> Self {
+        let tuning = Tuning::default();
         PlayerControls {
-            jump_power: 1100.,
-            speed: 250.,
+            jump_power: tuning.jump_power,
+            speed: tuning.speed,
         }
     }
 }
 
-#[derive(Component)]
-struct AnimationTimer(Timer, usize);
+/// Why a run ended
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DeathCause {
+    Fell,
+    Starved,
+    Bird,
+}
+
+pub struct PlayerDied(pub DeathCause);
+
+/// What the pig is currently doing, every state has its own animation clip
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Deserialize)]
+pub enum PlayerState {
+    #[default]
+    Idle,
+    Run,
+    Jump,
+    Fall,
+    Land,
+    Eat,
+    Dig,
+    Hurt,
+    Dead,
+}
+
+impl PlayerState {
+    /// The pig stays in these states until the next run
+    fn is_final(&self) -> bool {
+        matches!(self, PlayerState::Hurt | PlayerState::Dead)
+    }
+}
+
+/// Seconds since the player entered its current [`PlayerState`]
+#[derive(Component, Default)]
+struct TimeInState(f32);
+
+/// Sent whenever the [`PlayerState`] changes
+pub struct PlayerStateChanged {
+    pub from: PlayerState,
+    pub to: PlayerState,
+    pub position: Vec2,
+}
 
 /// This plugin handles player related stuff like movement
 /// Player logic is only active during the State `GameState::Playing`
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<Hunger>()
+        app.add_event::<PlayerDied>()
+            .add_event::<PlayerStateChanged>()
+            .init_resource::<Hunger>()
             .init_resource::<PlayerControls>()
-            .init_resource::<HungerPerSecond>()
+            .init_resource::<GodMode>()
             .insert_resource(TakeInputs(true))
             .insert_resource(Falling(false))
             .add_system(spawn_player.in_schedule(OnEnter(GameState::Prepare)))
@@ -84,60 +130,129 @@ impl Plugin for PlayerPlugin {
                 )
                     .in_set(OnUpdate(GameState::Playing)),
             )
-            .add_system(
-                animate_player
-                    .after(apply_actions)
-                    .before(PhysicsSystems::Move),
-            );
+            // not only while playing, so the deaths that end a run are not missed
+            .add_system(update_player_state.after(PhysicsSystems::Move));
     }
 }
 
 fn lose_on_falling(
-    player: Query<&Transform, With<Player>>,
+    mut player: Query<&mut Transform, With<Player>>,
     mut state: ResMut<NextState<GameState>>,
+    mut player_died: EventWriter<PlayerDied>,
+    mut sounds: EventWriter<SoundEvent>,
+    god_mode: Res<GodMode>,
 ) {
-    if player.single().translation.y < -2. * TILE_SIZE {
+    let mut transform = player.single_mut();
+    if transform.translation.y < -2. * TILE_SIZE {
+        if god_mode.0 {
+            transform.translation.y = LEVEL_HEIGHT;
+            return;
+        }
+        player_died.send(PlayerDied(DeathCause::Fell));
+        sounds.send(SoundEvent::at(Sound::Death, transform.translation.xy()));
         state.set(GameState::Restart);
     }
 }
 
-fn animate_player(
+pub fn update_player_state(
     time: Res<Time>,
-    mut query: Query<(&mut AnimationTimer, &mut TextureAtlasSprite, &Velocity), With<Player>>,
+    actions: Res<Actions>,
+    mut player: Query<
+        (
+            &Transform,
+            &Velocity,
+            Option<&Grounded>,
+            &mut PlayerState,
+            &mut TimeInState,
+        ),
+        With<Player>,
+    >,
+    mut player_died: EventReader<PlayerDied>,
+    mut food_eaten: EventReader<FoodEaten>,
+    mut truffle_collected: EventReader<TruffleCollected>,
+    mut state_changed: EventWriter<PlayerStateChanged>,
 ) {
-    for (mut timer, mut sprite, velocity) in &mut query {
-        if velocity.0.length() < f32::EPSILON || velocity.0.y.abs() > 0. {
-            sprite.index = 0;
-            timer.0.reset();
-            continue;
+    let died = player_died.iter().last().map(|PlayerDied(cause)| *cause);
+    let ate = food_eaten.iter().count() + truffle_collected.iter().count() > 0;
+    let Ok((transform, velocity, grounded, mut state, mut time_in_state)) = player.get_single_mut()
+    else {
+        return;
+    };
+    time_in_state.0 += time.delta_seconds();
+
+    let current = *state;
+    let next = if current.is_final() {
+        current
+    } else if let Some(cause) = died {
+        if cause == DeathCause::Bird {
+            PlayerState::Hurt
+        } else {
+            PlayerState::Dead
         }
-        timer.0.tick(time.delta());
-        if timer.0.finished() {
-            sprite.index = (sprite.index + 1) % timer.1;
+    } else if grounded.is_none() {
+        if velocity.0.y > 0. {
+            PlayerState::Jump
+        } else {
+            PlayerState::Fall
         }
+    } else if ate {
+        PlayerState::Eat
+    } else if matches!(current, PlayerState::Jump | PlayerState::Fall) {
+        PlayerState::Land
+    } else if (current == PlayerState::Land && time_in_state.0 < LAND_SECONDS)
+        || (current == PlayerState::Eat && time_in_state.0 < EAT_SECONDS)
+    {
+        current
+    } else if actions.attempt_dig {
+        PlayerState::Dig
+    } else if velocity.0.x.abs() > 0. {
+        PlayerState::Run
+    } else {
+        PlayerState::Idle
+    };
+
+    if next != current || (next == PlayerState::Eat && ate) {
+        *state = next;
+        time_in_state.0 = 0.;
+        state_changed.send(PlayerStateChanged {
+            from: current,
+            to: next,
+            position: transform.translation.xy(),
+        });
     }
 }
 
 fn process_food(
     time: Res<Time>,
     mut state: ResMut<NextState<GameState>>,
-    hunger_per_second: Res<HungerPerSecond>,
+    tuning: Res<Tuning>,
     mut hunger: ResMut<Hunger>,
+    mut player_died: EventWriter<PlayerDied>,
+    mut sounds: EventWriter<SoundEvent>,
+    god_mode: Res<GodMode>,
 ) {
-    hunger.0 -= hunger_per_second.0 * time.delta_seconds();
-    if hunger.0 < 0. {
+    hunger.0 -= tuning.hunger_per_second * time.delta_seconds();
+    if god_mode.0 {
+        hunger.0 = hunger.0.max(0.);
+    } else if hunger.0 < 0. {
+        player_died.send(PlayerDied(DeathCause::Starved));
+        sounds.send(SoundEvent::centred(Sound::Death));
         state.set(GameState::Restart);
     }
 }
 
-fn spawn_player(mut commands: Commands, textures: Res<TextureAssets>) {
+fn spawn_player(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    start_chunk: Res<StartChunk>,
+) {
     commands
         .spawn(SpriteSheetBundle {
             texture_atlas: textures.pig.clone(),
             sprite: TextureAtlasSprite::new(0),
             transform: {
                 let mut transform =
-                    Transform::from_translation(Vec3::new(WIDTH / 2., HEIGHT / 2., PLAYER_Z));
+                    Transform::from_translation(start_chunk.player_start().extend(PLAYER_Z));
                 transform.scale = Vec3::splat(2.);
                 transform
             },
@@ -149,25 +264,28 @@ fn spawn_player(mut commands: Commands, textures: Res<TextureAssets>) {
         .insert(Player)
         .insert(Move)
         .insert(Velocity(Vec2::ZERO))
-        .insert(AnimationTimer(
-            Timer::from_seconds(0.15, TimerMode::Repeating),
-            4,
-        ));
+        .insert(PlayerState::default())
+        .insert(TimeInState::default());
 }
 
 fn apply_actions(
     actions: Res<Actions>,
     player_controls: Res<PlayerControls>,
-    mut player_query: Query<(Entity, &mut Velocity, &mut TextureAtlasSprite), With<Player>>,
+    mut player_query: Query<
+        (Entity, &Transform, &mut Velocity, &mut TextureAtlasSprite),
+        With<Player>,
+    >,
     can_jump: Query<&Grounded, With<Player>>,
+    mut sounds: EventWriter<SoundEvent>,
 ) {
-    let (player, mut velocity, mut sprite) = player_query.single_mut();
+    let (player, transform, mut velocity, mut sprite) = player_query.single_mut();
     velocity.0.x = actions.player_movement * player_controls.speed;
     if velocity.0.x.abs() > 0. {
         sprite.flip_x = velocity.0.x < 0.;
     }
     if actions.attempt_jump && can_jump.contains(player) {
         velocity.0.y = player_controls.jump_power;
+        sounds.send(SoundEvent::at(Sound::Jump, transform.translation.xy()));
     }
 }
 
@@ -175,13 +293,24 @@ fn bird_kill(
     bird: Query<(&Transform, &Collider), (With<Bird>, Without<Player>)>,
     player: Query<(&Transform, &Collider), (With<Player>, Without<Bird>)>,
     mut state: ResMut<NextState<GameState>>,
+    mut player_died: EventWriter<PlayerDied>,
+    mut sounds: EventWriter<SoundEvent>,
+    god_mode: Res<GodMode>,
 ) {
+    if god_mode.0 {
+        return;
+    }
     let (player_transform, player_collider) = player.single();
     let player_rec =
         Rect::from_center_size(player_transform.translation.xy(), player_collider.size);
     for (bird_transform, bird_collider) in &bird {
         let bird_rec = Rect::from_center_size(bird_transform.translation.xy(), bird_collider.size);
         if !bird_rec.intersect(player_rec).is_empty() {
+            player_died.send(PlayerDied(DeathCause::Bird));
+            sounds.send(SoundEvent::at(
+                Sound::Death,
+                player_transform.translation.xy(),
+            ));
             state.set(GameState::Restart);
         }
     }
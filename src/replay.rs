@@ -0,0 +1,440 @@
+use crate::actions::Actions;
+use crate::loading::FontAssets;
+use crate::menu::ButtonColors;
+use crate::physics::PhysicsSystems;
+use crate::reset::run_finished;
+use crate::seed::{reseed, RunSeed, SeedOverride};
+use crate::settings::config_path;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy::time::{TimeSystem, TimeUpdateStrategy};
+use bevy::utils::{Duration, Instant};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const LAST_RUN_FILE: &str = "last_run.replay";
+const REPLAY_MAGIC: &[u8; 4] = b"TRRP";
+const REPLAY_VERSION: u8 = 1;
+/// Delta in nanoseconds, movement and a byte of flags
+const TICK_LEN: usize = 9;
+/// Recorded ticks played per frame while fast forwarding
+const FAST_FORWARD_SPEED: usize = 4;
+
+pub struct ReplayPlugin;
+
+/// This plugin records the actions of every run and can play them back
+/// Replays reuse the run seed and feed the recorded frame times to `Time`, so they play out exactly
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Recorder>()
+            .add_systems(
+                (stop_replay, start_pending_replay.after(stop_replay))
+                    .in_schedule(OnEnter(GameState::Menu)),
+            )
+            .add_system(
+                start_recording
+                    .after(reseed)
+                    .in_schedule(OnEnter(GameState::Prepare)),
+            )
+            .add_system(
+                start_recording
+                    .after(reseed)
+                    .in_schedule(OnExit(GameState::Restart)),
+            )
+            .add_system(
+                record_tick
+                    .after(PhysicsSystems::CalculateVelocities)
+                    .in_set(OnUpdate(GameState::Playing))
+                    .run_if(not(resource_exists::<Replay>())),
+            )
+            .add_systems(
+                (
+                    save_recording.run_if(run_finished),
+                    stop_replay.after(save_recording),
+                )
+                    .in_schedule(OnEnter(GameState::Restart)),
+            )
+            .add_system(
+                fast_forward_replay
+                    .after(CoreSchedule::outer_loop)
+                    .in_schedule(CoreSchedule::Outer),
+            )
+            .add_system(
+                drive_replay_clock
+                    .in_base_set(CoreSet::First)
+                    .before(TimeSystem)
+                    .run_if(resource_exists::<Replay>()),
+            )
+            .add_systems(
+                (
+                    feed_actions.before(PhysicsSystems::CalculateVelocities),
+                    spawn_replay_hud,
+                    replay_controls,
+                    update_replay_hud,
+                )
+                    .in_set(OnUpdate(GameState::Playing))
+                    .distributive_run_if(resource_exists::<Replay>()),
+            );
+    }
+}
+
+/// The [`Actions`] of a single frame and how long that frame took
+#[derive(Clone, Copy)]
+struct Tick {
+    delta_nanos: u32,
+    player_movement: f32,
+    attempt_jump: bool,
+    attempt_dig: bool,
+}
+
+impl Tick {
+    fn delta(&self) -> Duration {
+        Duration::from_nanos(self.delta_nanos as u64)
+    }
+}
+
+/// Everything needed to play a run again
+#[derive(Clone, Default)]
+pub struct Recording {
+    seed: u64,
+    ticks: Vec<Tick>,
+}
+
+impl Recording {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Recording::from_bytes(&fs::read(path)?)
+    }
+
+    fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_bytes())
+    }
+
+    fn duration(&self) -> Duration {
+        self.ticks.iter().map(Tick::delta).sum()
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(REPLAY_MAGIC.len() + 9 + self.ticks.len() * TICK_LEN);
+        bytes.extend_from_slice(REPLAY_MAGIC);
+        bytes.push(REPLAY_VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        for tick in &self.ticks {
+            bytes.extend_from_slice(&tick.delta_nanos.to_le_bytes());
+            bytes.extend_from_slice(&tick.player_movement.to_le_bytes());
+            bytes.push(u8::from(tick.attempt_jump) | u8::from(tick.attempt_dig) << 1);
+        }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message);
+        let Some(rest) = bytes.strip_prefix(REPLAY_MAGIC.as_slice()) else {
+            return Err(invalid("not a replay file"));
+        };
+        let [version, rest @ ..] = rest else {
+            return Err(invalid("missing replay version"));
+        };
+        if *version != REPLAY_VERSION {
+            return Err(invalid("unsupported replay version"));
+        }
+        if rest.len() < 8 || (rest.len() - 8) % TICK_LEN != 0 {
+            return Err(invalid("truncated replay"));
+        }
+        let (seed, ticks) = rest.split_at(8);
+        Ok(Recording {
+            seed: u64::from_le_bytes(seed.try_into().unwrap()),
+            ticks: ticks
+                .chunks_exact(TICK_LEN)
+                .map(|tick| Tick {
+                    delta_nanos: u32::from_le_bytes(tick[0..4].try_into().unwrap()),
+                    player_movement: f32::from_le_bytes(tick[4..8].try_into().unwrap()),
+                    attempt_jump: tick[8] & 1 != 0,
+                    attempt_dig: tick[8] & 2 != 0,
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Records the current run and keeps the last finished one around for the restart screen
+#[derive(Resource, Default)]
+pub struct Recorder {
+    current: Recording,
+    last: Option<Recording>,
+}
+
+impl Recorder {
+    pub fn last(&self) -> Option<&Recording> {
+        self.last.as_ref()
+    }
+}
+
+/// A replay loaded from the command line, started as soon as the menu is reached
+#[derive(Resource)]
+pub struct PendingReplay(pub Recording);
+
+/// Present while a recording is played back
+#[derive(Resource)]
+pub struct Replay {
+    recording: Recording,
+    tick: usize,
+    played: Duration,
+    length: Duration,
+    /// The instant handed to `Time` for the last replayed frame
+    clock: Option<Instant>,
+    fast_forward: bool,
+    previous_seed: Option<u64>,
+}
+
+/// Play the recording with the next run
+pub fn start_replay(
+    commands: &mut Commands,
+    seed_override: &mut SeedOverride,
+    recording: Recording,
+) {
+    info!("Replaying run with seed {}", recording.seed);
+    let previous_seed = seed_override.0.replace(recording.seed);
+    commands.insert_resource(Replay {
+        tick: 0,
+        played: Duration::ZERO,
+        length: recording.duration(),
+        clock: None,
+        fast_forward: false,
+        previous_seed,
+        recording,
+    });
+}
+
+fn start_pending_replay(
+    mut commands: Commands,
+    pending: Option<Res<PendingReplay>>,
+    mut seed_override: ResMut<SeedOverride>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    let Some(pending) = pending else {
+        return;
+    };
+    start_replay(&mut commands, &mut seed_override, pending.0.clone());
+    commands.remove_resource::<PendingReplay>();
+    state.set(GameState::Prepare);
+}
+
+fn start_recording(
+    mut recorder: ResMut<Recorder>,
+    run_seed: Res<RunSeed>,
+    replay: Option<Res<Replay>>,
+) {
+    if replay.is_some() {
+        return;
+    }
+    recorder.current = Recording {
+        seed: run_seed.0,
+        ticks: vec![],
+    };
+}
+
+fn record_tick(mut recorder: ResMut<Recorder>, actions: Res<Actions>, time: Res<Time>) {
+    recorder.current.ticks.push(Tick {
+        // frames longer than four seconds do not happen outside of a debugger
+        delta_nanos: u32::try_from(time.delta().as_nanos()).unwrap_or(u32::MAX),
+        player_movement: actions.player_movement,
+        attempt_jump: actions.attempt_jump,
+        attempt_dig: actions.attempt_dig,
+    });
+}
+
+fn save_recording(mut recorder: ResMut<Recorder>, replay: Option<Res<Replay>>) {
+    if replay.is_some() || recorder.current.ticks.is_empty() {
+        return;
+    }
+    let recording = std::mem::take(&mut recorder.current);
+    if let Some(path) = config_path(LAST_RUN_FILE) {
+        match recording.save(&path) {
+            Ok(()) => info!("Saved replay of the last run to {path:?}"),
+            Err(error) => warn!("Failed to save replay to {path:?}: {error}"),
+        }
+    }
+    recorder.last = Some(recording);
+}
+
+/// Hands the recorded frame time of the next tick to `Time`
+///
+/// Frames that do not consume a tick advance the clock as well, which is harmless since no
+/// gameplay system runs in them.
+fn drive_replay_clock(
+    mut replay: ResMut<Replay>,
+    time: Res<Time>,
+    mut strategy: ResMut<TimeUpdateStrategy>,
+) {
+    let Some(delta) = replay.recording.ticks.get(replay.tick).map(Tick::delta) else {
+        return;
+    };
+    let clock = replay
+        .clock
+        .get_or_insert_with(|| time.last_update().unwrap_or_else(Instant::now));
+    *clock += delta;
+    *strategy = TimeUpdateStrategy::ManualInstant(*clock);
+}
+
+fn feed_actions(
+    mut replay: ResMut<Replay>,
+    mut actions: ResMut<Actions>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    if let Some(tick) = replay.recording.ticks.get(replay.tick).copied() {
+        actions.player_movement = tick.player_movement;
+        actions.attempt_jump = tick.attempt_jump;
+        actions.attempt_dig = tick.attempt_dig;
+        replay.played += tick.delta();
+        replay.tick += 1;
+    }
+    if replay.tick >= replay.recording.ticks.len() {
+        info!("Replay finished");
+        state.set(GameState::Restart);
+    }
+}
+
+/// Runs the game for the extra ticks of a fast forwarded frame before it is rendered
+///
+/// Every tick goes through the whole schedule with its recorded frame time, so fast forward
+/// plays back just as exactly and speeds up the same on any machine.
+fn fast_forward_replay(world: &mut World) {
+    for _ in 1..FAST_FORWARD_SPEED {
+        let fast_forward = world
+            .get_resource::<Replay>()
+            .map_or(false, |replay| replay.fast_forward);
+        if !fast_forward || world.resource::<State<GameState>>().0 != GameState::Playing {
+            return;
+        }
+        world.run_schedule(CoreSchedule::Main);
+    }
+}
+
+pub fn stop_replay(
+    mut commands: Commands,
+    replay: Option<Res<Replay>>,
+    mut seed_override: ResMut<SeedOverride>,
+    mut strategy: ResMut<TimeUpdateStrategy>,
+    hud: Query<Entity, With<ReplayHud>>,
+) {
+    let Some(replay) = replay else {
+        return;
+    };
+    seed_override.0 = replay.previous_seed;
+    *strategy = TimeUpdateStrategy::Automatic;
+    commands.remove_resource::<Replay>();
+    for entity in &hud {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[derive(Component)]
+struct ReplayHud;
+
+#[derive(Component)]
+struct ReplayText;
+
+#[derive(Component, Clone, Copy)]
+enum ReplayButton {
+    Pause,
+    FastForward,
+}
+
+fn spawn_replay_hud(
+    mut commands: Commands,
+    font_assets: Res<FontAssets>,
+    button_colors: Res<ButtonColors>,
+    hud: Query<(), With<ReplayHud>>,
+) {
+    if !hud.is_empty() {
+        return;
+    }
+    let text_style = TextStyle {
+        font: font_assets.fira_sans.clone(),
+        font_size: 24.0,
+        color: Color::rgb(0.9, 0.9, 0.9),
+    };
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.),
+                    bottom: Val::Px(10.),
+                    ..default()
+                },
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(ReplayHud)
+        .with_children(|parent| {
+            for (button, label) in [
+                (ReplayButton::Pause, "II"),
+                (ReplayButton::FastForward, ">>"),
+            ] {
+                parent
+                    .spawn(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(50.0), Val::Px(36.0)),
+                            margin: UiRect::right(Val::Px(10.)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: button_colors.normal.into(),
+                        ..default()
+                    })
+                    .insert(button)
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                    });
+            }
+            parent
+                .spawn(TextBundle::from_section("", text_style))
+                .insert(ReplayText);
+        });
+}
+
+/// Pausing opens the regular pause menu, fast forward plays several recorded ticks per frame
+fn replay_controls(
+    mut replay: ResMut<Replay>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut state: ResMut<NextState<GameState>>,
+    interaction_query: Query<(&Interaction, &ReplayButton), Changed<Interaction>>,
+) {
+    let mut toggle_fast_forward = keyboard_input.just_pressed(KeyCode::F);
+    for (interaction, button) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        match button {
+            ReplayButton::Pause => state.set(GameState::Paused),
+            ReplayButton::FastForward => toggle_fast_forward = true,
+        }
+    }
+    if toggle_fast_forward {
+        replay.fast_forward = !replay.fast_forward;
+    }
+}
+
+fn update_replay_hud(replay: Res<Replay>, mut text: Query<&mut Text, With<ReplayText>>) {
+    let Ok(mut text) = text.get_single_mut() else {
+        return;
+    };
+    text.sections[0].value = format!(
+        "Replay {:.1}s / {:.1}s{}",
+        replay.played.as_secs_f32(),
+        replay.length.as_secs_f32(),
+        if replay.fast_forward {
+            format!("  >> x{FAST_FORWARD_SPEED}")
+        } else {
+            String::new()
+        }
+    );
+}
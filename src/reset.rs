@@ -1,39 +1,78 @@
 use crate::loading::FontAssets;
-use crate::map::{CurrentChunk, Level};
+use crate::map::{CurrentChunk, Level, StartChunk};
 use crate::menu::ButtonColors;
 use crate::physics::Velocity;
-use crate::player::{Grounded, Hunger, Player, PLAYER_Z};
-use crate::ui::Score;
-use crate::{GameState, HEIGHT, WIDTH};
+use crate::player::{Grounded, Hunger, Player, PlayerState, PLAYER_Z};
+use crate::replay::{start_replay, Recorder};
+use crate::seed::SeedOverride;
+use crate::tuning::Tuning;
+use crate::ui::{Hud, Score};
+use crate::GameState;
 use bevy::prelude::*;
 
 pub struct ResetPlugin;
 
 impl Plugin for ResetPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            (
-                reset_player,
-                cleanup_restart,
-                reset_hunger,
-                reset_score,
-                reset_map,
+        app.init_resource::<AbandonedRun>()
+            .add_systems(
+                (
+                    reset_player,
+                    reset_hunger,
+                    reset_score,
+                    reset_map,
+                    clear_abandoned_run,
+                )
+                    .in_schedule(OnExit(GameState::Restart)),
             )
-                .in_schedule(OnExit(GameState::Restart)),
-        )
-        .add_system(setup_restart.in_schedule(OnEnter(GameState::Restart)))
-        .add_system(click_restart_button.in_set(OnUpdate(GameState::Restart)));
+            .add_systems(
+                (despawn_run, reset_hunger, reset_score, reset_map)
+                    .in_schedule(OnEnter(GameState::Menu)),
+            );
     }
 }
 
+pub struct RestartMenuPlugin;
+
+/// This plugin shows the "Again!" and "Replay" buttons after a run ended
+/// Without it, as in the headless simulation, the game waits in `GameState::Restart`
+impl Plugin for RestartMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(setup_restart.in_schedule(OnEnter(GameState::Restart)))
+            .add_system(click_restart_button.in_set(OnUpdate(GameState::Restart)))
+            .add_system(cleanup_restart.in_schedule(OnExit(GameState::Restart)));
+    }
+}
+
+/// Set when the run is restarted from the pause menu
+/// The "Again!" button is skipped and the next run starts right away
+#[derive(Resource, Default)]
+pub struct AbandonedRun(pub(crate) bool);
+
+/// Run condition for the systems that save a run on entering `GameState::Restart`
+pub fn run_finished(abandoned_run: Res<AbandonedRun>) -> bool {
+    !abandoned_run.0
+}
+
+#[derive(Component)]
+struct RestartMenu;
+
+#[derive(Component, Clone, Copy)]
+enum RestartButton {
+    Again,
+    Replay,
+}
+
 fn reset_player(
-    mut player: Query<(Entity, &mut Transform, &mut Velocity), With<Player>>,
+    mut player: Query<(Entity, &mut Transform, &mut Velocity, &mut PlayerState), With<Player>>,
     mut commands: Commands,
+    start_chunk: Res<StartChunk>,
 ) {
-    let (entity, mut transform, mut velocity) = player.single_mut();
+    let (entity, mut transform, mut velocity, mut state) = player.single_mut();
     commands.entity(entity).remove::<Grounded>();
-    transform.translation = Vec3::new(WIDTH / 2., HEIGHT / 2., PLAYER_Z);
+    transform.translation = start_chunk.player_start().extend(PLAYER_Z);
     velocity.0 = Vec2::ZERO;
+    *state = PlayerState::default();
 }
 
 fn reset_map(
@@ -42,13 +81,19 @@ fn reset_map(
     mut current_chunk: ResMut<CurrentChunk>,
 ) {
     for entity in &level {
-        commands.entity(entity).despawn();
+        commands.entity(entity).despawn_recursive();
     }
     current_chunk.0 = 0;
 }
 
-fn reset_hunger(mut hunger: ResMut<Hunger>) {
-    *hunger = Hunger::default();
+fn despawn_run(run: Query<Entity, Or<(With<Player>, With<Hud>)>>, mut commands: Commands) {
+    for entity in &run {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn reset_hunger(mut hunger: ResMut<Hunger>, tuning: Res<Tuning>) {
+    hunger.0 = tuning.max_hunger;
 }
 
 fn reset_score(mut score: ResMut<Score>) {
@@ -59,58 +104,84 @@ fn setup_restart(
     mut commands: Commands,
     font_assets: Res<FontAssets>,
     button_colors: Res<ButtonColors>,
+    recorder: Res<Recorder>,
+    abandoned_run: Res<AbandonedRun>,
+    mut state: ResMut<NextState<GameState>>,
 ) {
+    if abandoned_run.0 {
+        state.set(GameState::Playing);
+        return;
+    }
+    let text_style = TextStyle {
+        font: font_assets.fira_sans.clone(),
+        font_size: 40.0,
+        color: Color::rgb(0.9, 0.9, 0.9),
+    };
+    let mut buttons = vec![(RestartButton::Again, "Again!")];
+    if recorder.last().is_some() {
+        buttons.push((RestartButton::Replay, "Replay"));
+    }
     commands
-        .spawn(ButtonBundle {
+        .spawn(NodeBundle {
             style: Style {
-                size: Size::new(Val::Px(120.0), Val::Px(50.0)),
-                margin: UiRect::all(Val::Auto),
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
                 justify_content: JustifyContent::Center,
                 align_items: AlignItems::Center,
                 ..Default::default()
             },
-            background_color: button_colors.normal.into(),
             ..Default::default()
         })
+        .insert(RestartMenu)
         .with_children(|parent| {
-            parent.spawn(TextBundle::from_section(
-                "Again!",
-                TextStyle {
-                    font: font_assets.fira_sans.clone(),
-                    font_size: 40.0,
-                    color: Color::rgb(0.9, 0.9, 0.9),
-                },
-            ));
+            for (button, label) in buttons {
+                parent
+                    .spawn(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(140.0), Val::Px(50.0)),
+                            margin: UiRect::all(Val::Px(10.)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        background_color: button_colors.normal.into(),
+                        ..Default::default()
+                    })
+                    .insert(button)
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                    });
+            }
         });
 }
 
 fn click_restart_button(
-    button_colors: Res<ButtonColors>,
+    mut commands: Commands,
+    recorder: Res<Recorder>,
+    mut seed_override: ResMut<SeedOverride>,
     mut state: ResMut<NextState<GameState>>,
-    mut interaction_query: Query<
-        (&Interaction, &mut BackgroundColor),
-        (Changed<Interaction>, With<Button>),
-    >,
+    interaction_query: Query<(&Interaction, &RestartButton), Changed<Interaction>>,
     input: Res<Input<KeyCode>>,
 ) {
-    for (interaction, mut color) in &mut interaction_query {
-        match *interaction {
-            Interaction::Clicked => {
-                state.set(GameState::Playing);
-            }
-            Interaction::Hovered => {
-                *color = button_colors.hovered.into();
-            }
-            Interaction::None => {
-                *color = button_colors.normal.into();
-            }
+    for (interaction, button) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
         }
+        if let (RestartButton::Replay, Some(recording)) = (button, recorder.last()) {
+            start_replay(&mut commands, &mut seed_override, recording.clone());
+        }
+        state.set(GameState::Playing);
     }
     if input.just_pressed(KeyCode::Return) {
         state.set(GameState::Playing);
     }
 }
 
-fn cleanup_restart(mut commands: Commands, button: Query<Entity, With<Button>>) {
-    commands.entity(button.single()).despawn_recursive();
+fn clear_abandoned_run(mut abandoned_run: ResMut<AbandonedRun>) {
+    abandoned_run.0 = false;
+}
+
+fn cleanup_restart(mut commands: Commands, menu: Query<Entity, With<RestartMenu>>) {
+    for entity in &menu {
+        commands.entity(entity).despawn_recursive();
+    }
 }
@@ -0,0 +1,58 @@
+use crate::GameState;
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{random, SeedableRng};
+
+pub struct SeedPlugin;
+
+/// This plugin picks the seed of every run and seeds the gameplay random number generators with it
+/// Map generation and effects draw from separate generators, so their system order does not matter
+impl Plugin for SeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SeedOverride>()
+            .init_resource::<RunSeed>()
+            .init_resource::<MapRng>()
+            .init_resource::<EffectRng>()
+            .add_system(reseed.in_schedule(OnEnter(GameState::Prepare)))
+            .add_system(reseed.in_schedule(OnExit(GameState::Restart)));
+    }
+}
+
+/// Seed used for all following runs instead of a random one
+#[derive(Resource, Default)]
+pub struct SeedOverride(pub Option<u64>);
+
+/// Seed of the current run
+#[derive(Resource, Default)]
+pub struct RunSeed(pub u64);
+
+#[derive(Resource)]
+pub struct MapRng(pub StdRng);
+
+impl Default for MapRng {
+    fn default() -> Self {
+        MapRng(StdRng::seed_from_u64(0))
+    }
+}
+
+#[derive(Resource)]
+pub struct EffectRng(pub StdRng);
+
+impl Default for EffectRng {
+    fn default() -> Self {
+        EffectRng(StdRng::seed_from_u64(0))
+    }
+}
+
+pub fn reseed(
+    seed_override: Res<SeedOverride>,
+    mut run_seed: ResMut<RunSeed>,
+    mut map_rng: ResMut<MapRng>,
+    mut effect_rng: ResMut<EffectRng>,
+) {
+    run_seed.0 = seed_override.0.unwrap_or_else(random);
+    info!("Starting run with seed {}", run_seed.0);
+    map_rng.0 = StdRng::seed_from_u64(run_seed.0);
+    // a different stream than the map, otherwise both would make the same decisions
+    effect_rng.0 = StdRng::seed_from_u64(run_seed.0 ^ 0x9E37_79B9_7F4A_7C15);
+}
@@ -0,0 +1,431 @@
+use crate::actions::{ControlInput, GameControl};
+use crate::loading::FontAssets;
+use crate::menu::{spawn_overlay, ButtonColors, OVERLAY_Z};
+use bevy::prelude::*;
+use bevy::window::{PresentMode, PrimaryWindow, WindowMode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+pub struct SettingsPlugin;
+
+/// This plugin shows the settings screen on top of the main or pause menu
+/// Changes are applied right away and written to the config file when the screen is closed
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .add_state::<SettingsMenuState>()
+            .add_system(setup_settings_menu.in_schedule(OnEnter(SettingsMenuState::Open)))
+            .add_systems(
+                (click_settings_buttons, update_setting_texts, close_on_back)
+                    .in_set(OnUpdate(SettingsMenuState::Open)),
+            )
+            .add_system(cleanup_settings_menu.in_schedule(OnExit(SettingsMenuState::Open)))
+            .add_system(apply_window_settings);
+    }
+}
+
+#[derive(States, Default, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum SettingsMenuState {
+    #[default]
+    Closed,
+    Open,
+    Controls,
+}
+
+/// User settings that are persisted in the config directory
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Settings {
+    pub master_volume: f64,
+    pub music_volume: f64,
+    pub sfx_volume: f64,
+    pub fullscreen: bool,
+    pub resolution_scale: f32,
+    pub vsync: bool,
+    /// Camera shakes and zoom punches, turned off for reduced motion
+    pub screen_shake: bool,
+    /// Shows the terrain ahead on a strip at the bottom of the screen
+    pub minimap: bool,
+    /// Silences all audio for this session only
+    #[serde(skip)]
+    pub muted: bool,
+    /// Fullscreen for this session only, until fullscreen is toggled in the menu
+    #[serde(skip)]
+    pub force_fullscreen: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            master_volume: 1.,
+            music_volume: 1.,
+            sfx_volume: 1.,
+            fullscreen: false,
+            resolution_scale: 1.,
+            vsync: true,
+            screen_shake: true,
+            minimap: true,
+            muted: false,
+            force_fullscreen: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Read the settings from the config file, falling back to defaults
+    pub fn load() -> Self {
+        load_config(SETTINGS_FILE)
+    }
+
+    pub fn save(&self) {
+        save_config(SETTINGS_FILE, self)
+    }
+
+    pub fn music_volume(&self) -> f64 {
+        self.master_volume() * self.music_volume
+    }
+
+    pub fn sfx_volume(&self) -> f64 {
+        self.master_volume() * self.sfx_volume
+    }
+
+    fn master_volume(&self) -> f64 {
+        if self.muted {
+            0.
+        } else {
+            self.master_volume
+        }
+    }
+
+    fn is_fullscreen(&self) -> bool {
+        self.fullscreen || self.force_fullscreen
+    }
+
+    pub fn apply_to_window(&self, window: &mut Window) {
+        window.mode = if self.is_fullscreen() {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        };
+        window.present_mode = if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+        window
+            .resolution
+            .set_scale_factor_override(Some(self.resolution_scale as f64));
+    }
+}
+
+/// Path of a file in the game's config directory
+///
+/// There is no config directory on the web, so nothing is persisted there
+pub fn config_path(file: &str) -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("truffle_run").join(file))
+}
+
+/// Reads a file from the config directory, `None` if it is missing or cannot be parsed
+pub fn read_config<T: DeserializeOwned>(file: &str) -> Option<T> {
+    let path = config_path(file)?;
+    let content = fs::read_to_string(&path).ok()?;
+    ron::from_str(&content)
+        .map_err(|error| warn!("Failed to parse {path:?}: {error}"))
+        .ok()
+}
+
+/// Reads a file from the config directory, falling back to the default
+pub fn load_config<T: DeserializeOwned + Default>(file: &str) -> T {
+    read_config(file).unwrap_or_default()
+}
+
+pub fn save_config<T: Serialize>(file: &str, value: &T) {
+    let Some(path) = config_path(file) else {
+        return;
+    };
+    let content = match ron::ser::to_string_pretty(value, Default::default()) {
+        Ok(content) => content,
+        Err(error) => {
+            warn!("Failed to serialize {file}: {error}");
+            return;
+        }
+    };
+    if let Err(error) = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&path, content))
+    {
+        warn!("Failed to save {path:?}: {error}");
+    }
+}
+
+fn apply_window_settings(
+    settings: Res<Settings>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !settings.is_changed() || settings.is_added() {
+        return;
+    }
+    if let Ok(mut window) = window.get_single_mut() {
+        settings.apply_to_window(&mut window);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Setting {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    Fullscreen,
+    ResolutionScale,
+    Vsync,
+    ScreenShake,
+    Minimap,
+}
+
+impl Setting {
+    const ALL: [Setting; 8] = [
+        Setting::MasterVolume,
+        Setting::MusicVolume,
+        Setting::SfxVolume,
+        Setting::Fullscreen,
+        Setting::ResolutionScale,
+        Setting::Vsync,
+        Setting::ScreenShake,
+        Setting::Minimap,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Setting::MasterVolume => "Volume",
+            Setting::MusicVolume => "Music",
+            Setting::SfxVolume => "Effects",
+            Setting::Fullscreen => "Fullscreen",
+            Setting::ResolutionScale => "Scale",
+            Setting::Vsync => "VSync",
+            Setting::ScreenShake => "Shake & zoom",
+            Setting::Minimap => "Minimap",
+        }
+    }
+
+    fn value(&self, settings: &Settings) -> String {
+        let on_off = |value: bool| String::from(if value { "On" } else { "Off" });
+        match self {
+            Setting::MasterVolume => format!("{:.0}%", settings.master_volume * 100.),
+            Setting::MusicVolume => format!("{:.0}%", settings.music_volume * 100.),
+            Setting::SfxVolume => format!("{:.0}%", settings.sfx_volume * 100.),
+            Setting::Fullscreen => on_off(settings.is_fullscreen()),
+            Setting::ResolutionScale => format!("{:.2}x", settings.resolution_scale),
+            Setting::Vsync => on_off(settings.vsync),
+            Setting::ScreenShake => on_off(settings.screen_shake),
+            Setting::Minimap => on_off(settings.minimap),
+        }
+    }
+
+    /// Step the setting up or down; toggles ignore the direction
+    fn change(&self, settings: &mut Settings, up: bool) {
+        let step = |value: &mut f64| {
+            *value = (*value + if up { 0.1 } else { -0.1 }).clamp(0., 1.);
+        };
+        match self {
+            Setting::MasterVolume => step(&mut settings.master_volume),
+            Setting::MusicVolume => step(&mut settings.music_volume),
+            Setting::SfxVolume => step(&mut settings.sfx_volume),
+            Setting::Fullscreen => {
+                settings.fullscreen = !settings.is_fullscreen();
+                settings.force_fullscreen = false;
+            }
+            Setting::ResolutionScale => {
+                settings.resolution_scale =
+                    (settings.resolution_scale + if up { 0.25 } else { -0.25 }).clamp(0.5, 3.);
+            }
+            Setting::Vsync => settings.vsync = !settings.vsync,
+            Setting::ScreenShake => settings.screen_shake = !settings.screen_shake,
+            Setting::Minimap => settings.minimap = !settings.minimap,
+        }
+    }
+}
+
+#[derive(Component)]
+struct SettingsMenu;
+
+#[derive(Component, Clone, Copy)]
+enum SettingsButton {
+    Decrease(Setting),
+    Increase(Setting),
+    Controls,
+    Back,
+}
+
+#[derive(Component)]
+struct SettingText(Setting);
+
+fn setup_settings_menu(
+    mut commands: Commands,
+    font_assets: Res<FontAssets>,
+    button_colors: Res<ButtonColors>,
+    settings: Res<Settings>,
+) {
+    let text_style = TextStyle {
+        font: font_assets.fira_sans.clone(),
+        font_size: 30.0,
+        color: Color::rgb(0.9, 0.9, 0.9),
+    };
+    let button_style = Style {
+        size: Size::new(Val::Px(40.0), Val::Px(40.0)),
+        margin: UiRect::horizontal(Val::Px(5.)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..Default::default()
+    };
+    spawn_overlay(&mut commands, OVERLAY_Z)
+        .insert(SettingsMenu)
+        .with_children(|parent| {
+            for setting in Setting::ALL {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(420.0), Val::Px(50.0)),
+                            justify_content: JustifyContent::SpaceBetween,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            setting.label(),
+                            text_style.clone(),
+                        ));
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    align_items: AlignItems::Center,
+                                    ..Default::default()
+                                },
+                                ..Default::default()
+                            })
+                            .with_children(|parent| {
+                                parent
+                                    .spawn(ButtonBundle {
+                                        style: button_style.clone(),
+                                        background_color: button_colors.normal.into(),
+                                        ..Default::default()
+                                    })
+                                    .insert(SettingsButton::Decrease(setting))
+                                    .with_children(|parent| {
+                                        parent.spawn(TextBundle::from_section(
+                                            "<",
+                                            text_style.clone(),
+                                        ));
+                                    });
+                                parent
+                                    .spawn(
+                                        TextBundle::from_section(
+                                            setting.value(&settings),
+                                            text_style.clone(),
+                                        )
+                                        .with_style(Style {
+                                            size: Size::new(Val::Px(90.0), Val::Auto),
+                                            ..Default::default()
+                                        })
+                                        .with_text_alignment(TextAlignment::Center),
+                                    )
+                                    .insert(SettingText(setting));
+                                parent
+                                    .spawn(ButtonBundle {
+                                        style: button_style.clone(),
+                                        background_color: button_colors.normal.into(),
+                                        ..Default::default()
+                                    })
+                                    .insert(SettingsButton::Increase(setting))
+                                    .with_children(|parent| {
+                                        parent.spawn(TextBundle::from_section(
+                                            ">",
+                                            text_style.clone(),
+                                        ));
+                                    });
+                            });
+                    });
+            }
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        margin: UiRect::top(Val::Px(20.)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    for (button, label) in [
+                        (SettingsButton::Controls, "Controls"),
+                        (SettingsButton::Back, "Back"),
+                    ] {
+                        parent
+                            .spawn(ButtonBundle {
+                                style: Style {
+                                    size: Size::new(Val::Px(150.0), Val::Px(50.0)),
+                                    margin: UiRect::horizontal(Val::Px(10.)),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..Default::default()
+                                },
+                                background_color: button_colors.normal.into(),
+                                ..Default::default()
+                            })
+                            .insert(button)
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                            });
+                    }
+                });
+        });
+}
+
+fn click_settings_buttons(
+    mut settings: ResMut<Settings>,
+    mut state: ResMut<NextState<SettingsMenuState>>,
+    interaction_query: Query<(&Interaction, &SettingsButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        match button {
+            SettingsButton::Decrease(setting) => setting.change(&mut settings, false),
+            SettingsButton::Increase(setting) => setting.change(&mut settings, true),
+            SettingsButton::Controls => state.set(SettingsMenuState::Controls),
+            SettingsButton::Back => state.set(SettingsMenuState::Closed),
+        }
+    }
+}
+
+fn update_setting_texts(settings: Res<Settings>, mut texts: Query<(&mut Text, &SettingText)>) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (mut text, setting) in &mut texts {
+        text.sections[0].value = setting.0.value(&settings);
+    }
+}
+
+fn close_on_back(control_input: ControlInput, mut state: ResMut<NextState<SettingsMenuState>>) {
+    if control_input.just_pressed(GameControl::Back) {
+        state.set(SettingsMenuState::Closed);
+    }
+}
+
+fn cleanup_settings_menu(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    menu: Query<Entity, With<SettingsMenu>>,
+) {
+    settings.save();
+    for entity in &menu {
+        commands.entity(entity).despawn_recursive();
+    }
+}
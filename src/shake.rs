@@ -0,0 +1,158 @@
+use crate::camera::{follow_player, GameCamera};
+use crate::food::TruffleCollected;
+use crate::physics::{PhysicsSystems, Velocity};
+use crate::player::{DeathCause, Grounded, Player, PlayerDied};
+use crate::settings::Settings;
+use bevy::prelude::*;
+use bevy_parallax::ParallaxSystems;
+
+/// Camera offset at full trauma
+const MAX_OFFSET: f32 = 16.;
+/// Camera roll at full trauma, in radians
+const MAX_ROLL: f32 = 0.03;
+/// Trauma lost per second
+const TRAUMA_DECAY: f32 = 1.5;
+/// Higher values end zoom punches faster
+const ZOOM_DECAY: f32 = 10.;
+/// Zoom punches end once they are smaller than this
+const MIN_ZOOM: f32 = 0.001;
+/// How fast the shake wobbles
+const SHAKE_FREQUENCY: f32 = 25.;
+/// Landing faster than this shakes the camera, a normal jump lands at about the jump power
+const HARD_LANDING_SPEED: f32 = 1400.;
+
+pub struct ShakePlugin;
+
+/// This plugin shakes and zooms the camera on [`CameraShake`] events, unless screen shake is off
+/// The shake is removed again before the camera follows the player, so it never adds up
+impl Plugin for ShakePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CameraShake>()
+            .init_resource::<Shake>()
+            .add_system(remove_shake.before(follow_player))
+            .add_system(apply_shake.after(ParallaxSystems))
+            .add_system(shake_on_hard_landing.after(PhysicsSystems::Move))
+            .add_system(shake_on_bird.run_if(on_event::<PlayerDied>()))
+            .add_system(punch_on_truffle.run_if(on_event::<TruffleCollected>()));
+    }
+}
+
+/// Send to shake the camera, the trauma of several shakes adds up
+pub struct CameraShake {
+    /// Between 0 and 1, the shake grows with its square
+    pub trauma: f32,
+    /// Briefly zooms in by this fraction of the view
+    pub zoom: f32,
+}
+
+#[derive(Resource, Default)]
+struct Shake {
+    trauma: f32,
+    zoom: f32,
+    /// Offset currently added to the camera
+    offset: Vec2,
+    /// The camera is shaken or zoomed and has to be restored
+    applied: bool,
+}
+
+fn remove_shake(shake: Res<Shake>, mut camera: Query<&mut Transform, With<GameCamera>>) {
+    if !shake.applied {
+        return;
+    }
+    for mut transform in &mut camera {
+        transform.translation -= shake.offset.extend(0.);
+        transform.rotation = Quat::IDENTITY;
+    }
+}
+
+fn apply_shake(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut events: EventReader<CameraShake>,
+    mut shake: ResMut<Shake>,
+    mut camera: Query<(&mut Transform, &mut OrthographicProjection), With<GameCamera>>,
+) {
+    for event in events.iter() {
+        shake.trauma = (shake.trauma + event.trauma).min(1.);
+        shake.zoom = shake.zoom.max(event.zoom);
+    }
+    if !settings.screen_shake {
+        shake.trauma = 0.;
+        shake.zoom = 0.;
+    }
+    shake.trauma = (shake.trauma - TRAUMA_DECAY * time.delta_seconds()).max(0.);
+    shake.zoom *= (-ZOOM_DECAY * time.delta_seconds()).exp();
+    if shake.zoom < MIN_ZOOM {
+        shake.zoom = 0.;
+    }
+    if shake.trauma == 0. && shake.zoom == 0. {
+        // leave the camera alone once it is restored, other systems may change it too
+        if shake.applied {
+            shake.applied = false;
+            shake.offset = Vec2::ZERO;
+            for (_, mut projection) in &mut camera {
+                projection.scale = 1.;
+            }
+        }
+        return;
+    }
+
+    shake.applied = true;
+    let strength = shake.trauma * shake.trauma;
+    let t = time.elapsed_seconds() * SHAKE_FREQUENCY;
+    shake.offset = MAX_OFFSET * strength * Vec2::new(wobble(t, 0.), wobble(t, 10.));
+    for (mut transform, mut projection) in &mut camera {
+        transform.translation += shake.offset.extend(0.);
+        transform.rotation = Quat::from_rotation_z(MAX_ROLL * strength * wobble(t, 20.));
+        projection.scale = 1. - shake.zoom;
+    }
+}
+
+/// Smooth noise between -1 and 1, different for every seed
+fn wobble(t: f32, seed: f32) -> f32 {
+    ((t + seed).sin() + 0.5 * (2.3 * t + 1.7 * seed).sin()) / 1.5
+}
+
+fn shake_on_hard_landing(
+    player: Query<(&Velocity, Option<&Grounded>), With<Player>>,
+    mut falling_speed: Local<f32>,
+    mut shakes: EventWriter<CameraShake>,
+) {
+    let Ok((velocity, grounded)) = player.get_single() else {
+        return;
+    };
+    if grounded.is_none() {
+        *falling_speed = falling_speed.max(-velocity.0.y);
+        return;
+    }
+    if *falling_speed > HARD_LANDING_SPEED {
+        shakes.send(CameraShake {
+            trauma: (*falling_speed / HARD_LANDING_SPEED - 1.).min(0.6),
+            zoom: 0.,
+        });
+    }
+    *falling_speed = 0.;
+}
+
+fn shake_on_bird(mut player_died: EventReader<PlayerDied>, mut shakes: EventWriter<CameraShake>) {
+    for PlayerDied(cause) in player_died.iter() {
+        if *cause == DeathCause::Bird {
+            shakes.send(CameraShake {
+                trauma: 0.7,
+                zoom: 0.1,
+            });
+        }
+    }
+}
+
+fn punch_on_truffle(
+    mut truffle_collected: EventReader<TruffleCollected>,
+    mut shakes: EventWriter<CameraShake>,
+) {
+    for _ in truffle_collected.iter() {
+        shakes.send(CameraShake {
+            trauma: 0.,
+            zoom: 0.05,
+        });
+    }
+}
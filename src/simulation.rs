@@ -0,0 +1,160 @@
+//! Runs the game rules without a window, renderer or audio
+//!
+//! Time only moves when the simulation is stepped and every frame takes exactly [`FRAME`], so the
+//! same seed and the same actions always lead to the same run.
+
+use crate::bot::{Bot, BotPlugin};
+use crate::distance::metres;
+use crate::food::spawn_food;
+use crate::loading::TextureAssets;
+use crate::player::{Hunger, Player, PlayerDied};
+use crate::seed::SeedOverride;
+use crate::ui::Score;
+use crate::{GameState, GameplayPlugin};
+use bevy::ecs::event::ManualEventReader;
+use bevy::ecs::system::CommandQueue;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy::utils::{Duration, Instant};
+
+pub use crate::actions::Actions;
+pub use crate::map::{TUTORIAL_HOLE_END, TUTORIAL_HOLE_START};
+pub use crate::player::DeathCause;
+
+/// Length of a simulated frame
+pub const FRAME: Duration = Duration::from_nanos(16_666_667);
+
+pub struct HeadlessPlugin;
+
+/// This plugin adds the game rules on top of `MinimalPlugins`
+/// Nothing is ever drawn, so all assets are replaced by default handles
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TextureAssets {
+            // map generation picks a random food texture
+            food: vec![Handle::default(); 3],
+            ..default()
+        })
+        .init_resource::<Actions>()
+        .init_resource::<Score>()
+        .add_plugin(GameplayPlugin)
+        .add_plugin(BotPlugin);
+    }
+}
+
+pub struct Simulation {
+    app: App,
+    clock: Instant,
+    deaths: ManualEventReader<PlayerDied>,
+    death_cause: Option<DeathCause>,
+    furthest: f32,
+}
+
+impl Simulation {
+    pub fn new(seed: u64) -> Self {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugin(HeadlessPlugin);
+        app.world.resource_mut::<SeedOverride>().0 = Some(seed);
+        app.setup();
+        let mut simulation = Simulation {
+            app,
+            clock: Instant::now(),
+            deaths: default(),
+            death_cause: None,
+            furthest: 0.,
+        };
+        simulation.step();
+        simulation
+    }
+
+    /// Start a run and step until it is playing
+    pub fn start_run(&mut self) {
+        self.app
+            .world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Prepare);
+        self.death_cause = None;
+        self.furthest = 0.;
+        while self.state() != GameState::Playing {
+            self.step();
+        }
+    }
+
+    pub fn step(&mut self) {
+        self.clock += FRAME;
+        self.app
+            .insert_resource(TimeUpdateStrategy::ManualInstant(self.clock));
+        self.app.update();
+        let events = self.app.world.resource::<Events<PlayerDied>>();
+        if let Some(PlayerDied(cause)) = self.deaths.iter(events).last() {
+            self.death_cause = Some(*cause);
+        }
+        if self.is_running() {
+            self.furthest = self.furthest.max(self.player_position().x);
+        }
+    }
+
+    /// Step for the given number of seconds or until the run is over
+    pub fn run_for(&mut self, seconds: f32) {
+        let frames = (seconds / FRAME.as_secs_f32()).round() as usize;
+        for _ in 0..frames {
+            if !self.is_running() {
+                return;
+            }
+            self.step();
+        }
+    }
+
+    /// Let the bot choose the actions from now on
+    pub fn enable_bot(&mut self) {
+        self.app.init_resource::<Bot>();
+    }
+
+    /// The actions used for the following frames
+    pub fn actions_mut(&mut self) -> Mut<Actions> {
+        self.app.world.resource_mut::<Actions>()
+    }
+
+    pub fn state(&self) -> GameState {
+        self.app.world.resource::<State<GameState>>().0.clone()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.state() == GameState::Playing
+    }
+
+    pub fn hunger(&self) -> f32 {
+        self.app.world.resource::<Hunger>().0
+    }
+
+    pub fn score(&self) -> f32 {
+        self.app.world.resource::<Score>().0
+    }
+
+    /// Why the last run ended, if it did
+    pub fn death_cause(&self) -> Option<DeathCause> {
+        self.death_cause
+    }
+
+    /// Furthest distance reached in this run, in metres
+    pub fn distance(&self) -> f32 {
+        metres(self.furthest)
+    }
+
+    pub fn player_position(&mut self) -> Vec2 {
+        self.app
+            .world
+            .query_filtered::<&Transform, With<Player>>()
+            .single(&self.app.world)
+            .translation
+            .truncate()
+    }
+
+    pub fn spawn_food(&mut self, position: Vec2) {
+        let texture = self.app.world.resource::<TextureAssets>().food[0].clone();
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &self.app.world);
+        spawn_food(&mut commands, texture, position);
+        queue.apply(&mut self.app.world);
+    }
+}
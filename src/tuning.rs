@@ -0,0 +1,118 @@
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::BoxedFuture;
+use serde::Deserialize;
+
+pub struct TuningPlugin;
+
+/// This plugin loads `gameplay.tuning.ron` and keeps the [`Tuning`] resource in sync with it
+/// The file is watched, so changes apply while playing
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Tuning>()
+            .init_asset_loader::<TuningLoader>()
+            .add_system(apply_tuning);
+    }
+}
+
+/// Gameplay values that are balanced without recompiling
+///
+/// Missing fields in the file keep their default
+#[derive(Resource, Deserialize, TypeUuid, Clone, PartialEq, Debug)]
+#[uuid = "1ac3288c-5efc-49e3-9606-0cfbb692066a"]
+#[serde(default)]
+pub struct Tuning {
+    pub gravity: f32,
+    pub speed: f32,
+    pub jump_power: f32,
+    /// Speed during the `Faster` effect
+    pub faster_speed: f32,
+    /// Jump power during the `JumpPower` effect
+    pub boosted_jump_power: f32,
+    pub max_hunger: f32,
+    pub hunger_per_second: f32,
+    /// Hunger restored by a piece of food
+    pub food_value: f32,
+    /// Hunger restored by a truffle
+    pub truffle_value: f32,
+    pub bird_speed: f32,
+    pub effect_durations: EffectDurations,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Tuning {
+            gravity: 4250.,
+            speed: 250.,
+            jump_power: 1100.,
+            faster_speed: 350.,
+            boosted_jump_power: 1400.,
+            max_hunger: 100.,
+            hunger_per_second: 1.5,
+            food_value: 3.,
+            truffle_value: 5.,
+            bird_speed: 200.,
+            effect_durations: EffectDurations::default(),
+        }
+    }
+}
+
+/// Seconds that each effect lasts
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct EffectDurations {
+    pub faster: f32,
+    pub jump_power: f32,
+    pub shrink: f32,
+    pub grow: f32,
+    pub bird: f32,
+}
+
+impl Default for EffectDurations {
+    fn default() -> Self {
+        EffectDurations {
+            faster: 5.,
+            jump_power: 5.,
+            shrink: 10.,
+            grow: 10.,
+            bird: 30.,
+        }
+    }
+}
+
+#[derive(Default)]
+struct TuningLoader;
+
+impl AssetLoader for TuningLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let tuning = ron::de::from_bytes::<Tuning>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(tuning));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tuning.ron"]
+    }
+}
+
+fn apply_tuning(
+    mut events: EventReader<AssetEvent<Tuning>>,
+    assets: Res<Assets<Tuning>>,
+    mut tuning: ResMut<Tuning>,
+) {
+    for event in events.iter() {
+        if let AssetEvent::Created { handle } | AssetEvent::Modified { handle } = event {
+            if let Some(loaded) = assets.get(handle) {
+                info!("Applying gameplay tuning");
+                *tuning = loaded.clone();
+            }
+        }
+    }
+}
@@ -1,15 +1,37 @@
+use crate::food::{FoodEaten, TruffleCollected};
 use crate::loading::FontAssets;
+use crate::map::Level;
 use crate::player::Hunger;
+use crate::tuning::Tuning;
 use crate::GameState;
 use bevy::prelude::*;
 
+/// The hunger bar pulses below this
+const LOW_HUNGER: f32 = 20.;
+const POPUP_SECONDS: f32 = 0.8;
+/// How far popups float up before they are gone
+const POPUP_RISE: f32 = 60.;
+const POPUP_Z: f32 = 20.;
+const TEXT_COLOR: Color = Color::rgb(34. / 255., 32. / 255., 52. / 255.);
+const FRAME_COLOR: Color = Color::rgba(0.7, 0.7, 0.7, 0.7);
+
 pub struct UiPlugin;
 
+/// This plugin shows the score and hunger bar during a run
+/// Eating pops up the restored hunger next to the food
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems((spawn_score, spawn_hunger).in_schedule(OnEnter(GameState::Prepare)))
+        app.init_resource::<Score>()
+            .add_systems((spawn_score, spawn_hunger).in_schedule(OnEnter(GameState::Prepare)))
             .add_systems(
-                (update_score_text, update_hunger_text).in_set(OnUpdate(GameState::Playing)),
+                (
+                    update_score_text,
+                    bump_score,
+                    update_hunger_bar,
+                    spawn_popups,
+                    animate_popups,
+                )
+                    .in_set(OnUpdate(GameState::Playing)),
             );
     }
 }
@@ -17,11 +39,17 @@ impl Plugin for UiPlugin {
 #[derive(Resource, Default)]
 pub struct Score(pub(crate) f32);
 
+/// Marks the root nodes of the in-game UI
 #[derive(Component)]
-struct ScoreText;
+pub struct Hud;
+
+/// Grows when the score increases and shrinks back over time
+#[derive(Component, Default)]
+struct ScoreText {
+    bump: f32,
+}
 
 fn spawn_score(mut commands: Commands, font_assets: Res<FontAssets>) {
-    commands.init_resource::<Score>();
     commands
         .spawn(NodeBundle {
             style: Style {
@@ -36,14 +64,10 @@ fn spawn_score(mut commands: Commands, font_assets: Res<FontAssets>) {
                 },
                 ..Default::default()
             },
-            background_color: BackgroundColor(Color::Rgba {
-                red: 0.7,
-                green: 0.7,
-                blue: 0.7,
-                alpha: 0.7,
-            }),
+            background_color: FRAME_COLOR.into(),
             ..Default::default()
         })
+        .insert(Hud)
         .with_children(|parent| {
             parent
                 .spawn(TextBundle {
@@ -53,28 +77,52 @@ fn spawn_score(mut commands: Commands, font_assets: Res<FontAssets>) {
                             style: TextStyle {
                                 font: font_assets.fira_sans.clone(),
                                 font_size: 40.0,
-                                color: Color::rgb_u8(34, 32, 52),
+                                color: TEXT_COLOR,
                             },
                         }],
                         ..default()
                     },
                     ..Default::default()
                 })
-                .insert(ScoreText);
+                .insert(ScoreText::default());
         });
 }
 
-fn update_score_text(score: Res<Score>, mut score_text: Query<&mut Text, With<ScoreText>>) {
+fn update_score_text(
+    score: Res<Score>,
+    mut score_text: Query<(&mut Text, &mut ScoreText)>,
+    mut previous: Local<f32>,
+) {
     if !score.is_changed() {
         return;
     }
-    score_text.single_mut().sections[0].value = format!("{:.0}", score.0);
+    let (mut text, mut score_text) = score_text.single_mut();
+    text.sections[0].value = format!("{:.0}", score.0);
+    if score.0 > *previous {
+        score_text.bump = 1.;
+    }
+    *previous = score.0;
+}
+
+fn bump_score(time: Res<Time>, mut score_text: Query<(&mut Transform, &mut ScoreText)>) {
+    for (mut transform, mut score_text) in &mut score_text {
+        score_text.bump = (score_text.bump - 3. * time.delta_seconds()).max(0.);
+        transform.scale = Vec3::splat(1. + 0.4 * score_text.bump);
+    }
 }
 
 #[derive(Component)]
 struct HungerText;
 
-fn spawn_hunger(mut commands: Commands, font_assets: Res<FontAssets>) {
+/// Background of the hunger bar
+#[derive(Component)]
+struct HungerFrame;
+
+/// Filled part of the hunger bar
+#[derive(Component)]
+struct HungerFill;
+
+fn spawn_hunger(mut commands: Commands, font_assets: Res<FontAssets>, hunger: Res<Hunger>) {
     commands
         .spawn(NodeBundle {
             style: Style {
@@ -89,24 +137,37 @@ fn spawn_hunger(mut commands: Commands, font_assets: Res<FontAssets>) {
                 },
                 ..Default::default()
             },
-            background_color: BackgroundColor(Color::Rgba {
-                red: 0.7,
-                green: 0.7,
-                blue: 0.7,
-                alpha: 0.7,
-            }),
+            background_color: FRAME_COLOR.into(),
             ..Default::default()
         })
+        .insert(Hud)
+        .insert(HungerFrame)
         .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            left: Val::Px(0.),
+                            top: Val::Px(0.),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    background_color: hunger_color(1.).into(),
+                    ..default()
+                })
+                .insert(HungerFill);
             parent
                 .spawn(TextBundle {
                     text: Text {
                         sections: vec![TextSection {
-                            value: format!("{:.0}", Hunger::default().0),
+                            value: format!("{:.0}", hunger.0),
                             style: TextStyle {
                                 font: font_assets.fira_sans.clone(),
                                 font_size: 40.0,
-                                color: Color::rgb_u8(34, 32, 52),
+                                color: TEXT_COLOR,
                             },
                         }],
                         ..default()
@@ -117,9 +178,99 @@ fn spawn_hunger(mut commands: Commands, font_assets: Res<FontAssets>) {
         });
 }
 
-fn update_hunger_text(hunger: Res<Hunger>, mut hunger_text: Query<&mut Text, With<HungerText>>) {
-    if !hunger.is_changed() {
-        return;
+fn update_hunger_bar(
+    time: Res<Time>,
+    hunger: Res<Hunger>,
+    tuning: Res<Tuning>,
+    mut hunger_text: Query<&mut Text, With<HungerText>>,
+    mut fill: Query<(&mut Style, &mut BackgroundColor), With<HungerFill>>,
+    mut frame: Query<&mut BackgroundColor, (With<HungerFrame>, Without<HungerFill>)>,
+) {
+    let hunger = hunger.0.clamp(0., tuning.max_hunger);
+    let fraction = hunger / tuning.max_hunger;
+    hunger_text.single_mut().sections[0].value = format!("{hunger:.0}");
+    let (mut style, mut color) = fill.single_mut();
+    style.size.width = Val::Percent(100. * fraction);
+    *color = hunger_color(fraction).into();
+
+    let pulse = if hunger < LOW_HUNGER {
+        (0.5 + 0.5 * (8. * time.elapsed_seconds()).sin()) * (1. - hunger / LOW_HUNGER).max(0.5)
+    } else {
+        0.
+    };
+    let [red, green, blue, alpha] = FRAME_COLOR.as_rgba_f32();
+    *frame.single_mut() = Color::rgba(
+        red + (1. - red) * pulse,
+        green * (1. - pulse),
+        blue * (1. - pulse),
+        alpha,
+    )
+    .into();
+}
+
+/// Green when full, yellow at half and red when empty
+fn hunger_color(fraction: f32) -> Color {
+    let fraction = fraction.clamp(0., 1.);
+    if fraction > 0.5 {
+        let t = (fraction - 0.5) * 2.;
+        Color::rgb(0.95 - 0.6 * t, 0.8 - 0.05 * t, 0.2 + 0.1 * t)
+    } else {
+        let t = fraction * 2.;
+        Color::rgb(0.85 + 0.1 * t, 0.2 + 0.6 * t, 0.2)
+    }
+}
+
+/// Floating text showing the hunger restored by food and truffles
+#[derive(Component)]
+struct Popup {
+    timer: Timer,
+    start: Vec2,
+}
+
+fn spawn_popups(
+    mut commands: Commands,
+    font_assets: Res<FontAssets>,
+    mut food_eaten: EventReader<FoodEaten>,
+    mut truffle_collected: EventReader<TruffleCollected>,
+) {
+    let food = food_eaten.iter().map(|food| (food.position, food.value));
+    let truffles = truffle_collected
+        .iter()
+        .map(|truffle| (truffle.position, truffle.value));
+    for (position, value) in food.chain(truffles) {
+        commands
+            .spawn(Text2dBundle {
+                text: Text::from_section(
+                    format!("+{value:.0}"),
+                    TextStyle {
+                        font: font_assets.fira_sans.clone(),
+                        font_size: 30.0,
+                        color: TEXT_COLOR,
+                    },
+                ),
+                transform: Transform::from_translation(position.extend(POPUP_Z)),
+                ..default()
+            })
+            .insert(Popup {
+                timer: Timer::from_seconds(POPUP_SECONDS, TimerMode::Once),
+                start: position,
+            })
+            .insert(Level);
+    }
+}
+
+fn animate_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popups: Query<(Entity, &mut Popup, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut popup, mut transform, mut text) in &mut popups {
+        if popup.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let progress = popup.timer.percent();
+        transform.translation.y = popup.start.y + POPUP_RISE * progress;
+        text.sections[0].style.color.set_a(1. - progress * progress);
     }
-    hunger_text.single_mut().sections[0].value = format!("{:.0}", hunger.0.clamp(0., 100.));
 }
@@ -0,0 +1,89 @@
+use truffle_run::simulation::{Simulation, TUTORIAL_HOLE_END, TUTORIAL_HOLE_START};
+use truffle_run::{GameState, Tuning};
+
+fn run_right_until(simulation: &mut Simulation, x: f32) {
+    simulation.actions_mut().player_movement = 1.;
+    while simulation.is_running() && simulation.player_position().x < x {
+        simulation.step();
+    }
+}
+
+#[test]
+fn walking_into_the_tutorial_hole_loses() {
+    let mut simulation = Simulation::new(1);
+    simulation.start_run();
+    simulation.actions_mut().player_movement = 1.;
+    simulation.run_for(4.);
+    assert_eq!(simulation.state(), GameState::Restart);
+}
+
+#[test]
+fn jumping_over_the_tutorial_hole_succeeds() {
+    let mut simulation = Simulation::new(1);
+    simulation.start_run();
+    run_right_until(&mut simulation, TUTORIAL_HOLE_START - 20.);
+    simulation.actions_mut().attempt_jump = true;
+    simulation.run_for(0.1);
+    simulation.actions_mut().attempt_jump = false;
+    simulation.run_for(1.);
+    assert!(simulation.is_running());
+    assert!(simulation.player_position().x > TUTORIAL_HOLE_END);
+}
+
+#[test]
+fn standing_still_starves_after_about_67_seconds() {
+    let mut simulation = Simulation::new(1);
+    simulation.start_run();
+    simulation.run_for(66.);
+    assert!(simulation.is_running());
+    simulation.run_for(2.);
+    assert_eq!(simulation.state(), GameState::Restart);
+}
+
+#[test]
+fn eating_food_raises_hunger() {
+    let mut simulation = Simulation::new(1);
+    simulation.start_run();
+    simulation.run_for(10.);
+    let hunger = simulation.hunger();
+    let position = simulation.player_position();
+    simulation.spawn_food(position);
+    simulation.step();
+    assert!(simulation.hunger() > hunger + 2.);
+}
+
+#[test]
+fn same_seed_and_actions_give_the_same_run() {
+    let mut positions = vec![];
+    for _ in 0..2 {
+        let mut simulation = Simulation::new(7);
+        simulation.start_run();
+        run_right_until(&mut simulation, TUTORIAL_HOLE_START - 20.);
+        simulation.actions_mut().attempt_jump = true;
+        simulation.run_for(3.);
+        positions.push(simulation.player_position());
+    }
+    assert_eq!(positions[0], positions[1]);
+}
+
+#[test]
+fn the_bot_plays_until_it_dies() {
+    let mut simulation = Simulation::new(0);
+    simulation.enable_bot();
+    simulation.start_run();
+    simulation.run_for(15. * 60.);
+    assert!(simulation.death_cause().is_some());
+    // without the restart menu the game keeps waiting for the next run
+    for _ in 0..10 {
+        simulation.step();
+    }
+    assert_eq!(simulation.state(), GameState::Restart);
+}
+
+/// The simulation plays with the default tuning, so it has to match the one the game loads
+#[test]
+fn tuning_asset_matches_the_defaults() {
+    let asset = std::fs::read_to_string("assets/gameplay.tuning.ron").unwrap();
+    let tuning: Tuning = ron::from_str(&asset).unwrap();
+    assert_eq!(tuning, Tuning::default());
+}